@@ -0,0 +1,324 @@
+//! This module store a structured log of gameplay events. Every event is a
+//! JSON object on its own line, log of every session (launch of the game) is
+//! written to 'user://logs/<session id>.jsonl':
+//! ```json
+//! {"session":"1760870400-3fa2c81d","run":2,"level":"arena","time":12.48,"event":"squash","position":[3.1,0.5,-7.2],"combo":3,"score":1}
+//! ```
+//! Common fields are id of the session, number of the run in the session,
+//! id of the level and time since start of the run (in secs). Logs are
+//! aggregated to heatmaps by the report tool (see 'report' module).
+
+mod report;
+
+use crate::{player::DeathCause, stats::RunStats};
+use godot::{
+    classes::{DirAccess, FileAccess, RandomNumberGenerator, Time, file_access::ModeFlags},
+    global::Error,
+    prelude::*,
+};
+use std::{
+    fmt::Write,
+    sync::{
+        OnceLock,
+        atomic::{AtomicU32, Ordering},
+    },
+};
+
+/// Directory for logs.
+const LOG_DIRECTORY: &str = "user://logs";
+
+/// Extension of log files.
+const LOG_EXTENSION: &str = "jsonl";
+
+/// Id of the session, created on the first event.
+static SESSION_ID: OnceLock<String> = OnceLock::new();
+
+/// Number of the last run in the session.
+static LAST_RUN: AtomicU32 = AtomicU32::new(0);
+
+/// This enum store all events of the log.
+pub enum GameEvent {
+    /// Run of the level started, all next events belong to this run.
+    RunStarted { level_id: String, seed: u64 },
+
+    /// Run finished by death or complete of the level.
+    RunEnded { completed: bool, stats: RunStats },
+
+    /// Creep squashed by the player at position.
+    Squash {
+        position: Vector3,
+        combo: i64,
+        score: i64,
+    },
+
+    /// Player died at position.
+    Death {
+        position: Vector3,
+        cause: DeathCause,
+    },
+
+    /// Power-up ('shield' or 'slow') activated.
+    PowerUpGained { kind: &'static str },
+
+    /// Power-up ('shield' or 'slow') expired.
+    PowerUpExpired { kind: &'static str },
+
+    /// All goals of the level completed.
+    LevelComplete { time: f64, score: i64 },
+}
+
+/// This struct build a JSON object in one line.
+struct JsonObject(String);
+
+impl JsonObject {
+    fn new() -> Self {
+        Self("{".to_string())
+    }
+
+    /// Add key, values are added by other methods.
+    fn key(mut self, key: &str) -> Self {
+        if self.0.len() > 1 {
+            self.0.push(',');
+        }
+        self.0.push('"');
+        self.0.push_str(key);
+        self.0.push_str("\":");
+        self
+    }
+
+    fn string(mut self, key: &str, value: &str) -> Self {
+        self = self.key(key);
+        self.0.push('"');
+        for char in value.chars() {
+            match char {
+                '"' => self.0.push_str("\\\""),
+                '\\' => self.0.push_str("\\\\"),
+                char if char.is_control() => {
+                    let _ = write!(self.0, "\\u{:04x}", char as u32);
+                }
+                char => self.0.push(char),
+            }
+        }
+        self.0.push('"');
+        self
+    }
+
+    fn integer(mut self, key: &str, value: impl Into<i128>) -> Self {
+        self = self.key(key);
+        let _ = write!(self.0, "{}", value.into());
+        self
+    }
+
+    fn number(mut self, key: &str, value: f64) -> Self {
+        self = self.key(key);
+        push_number(&mut self.0, value);
+        self
+    }
+
+    fn boolean(mut self, key: &str, value: bool) -> Self {
+        self = self.key(key);
+        self.0.push_str(if value { "true" } else { "false" });
+        self
+    }
+
+    fn vector(mut self, key: &str, value: Vector3) -> Self {
+        self = self.key(key);
+        self.0.push('[');
+        for (index, axis) in [value.x, value.y, value.z].into_iter().enumerate() {
+            if index > 0 {
+                self.0.push(',');
+            }
+            push_number(&mut self.0, axis as f64);
+        }
+        self.0.push(']');
+        self
+    }
+
+    fn finish(mut self) -> String {
+        self.0.push('}');
+        self.0
+    }
+}
+
+/// Write number with 2 digits after point, JSON has no NaN and infinity.
+fn push_number(text: &mut String, value: f64) {
+    if value.is_finite() {
+        let _ = write!(text, "{}", (value * 100.0).round() / 100.0);
+    } else {
+        text.push_str("null");
+    }
+}
+
+impl GameEvent {
+    /// Encode event to JSON line with common fields of the run.
+    pub fn to_json(&self, session_id: &str, run: u32, level_id: &str, time: f64) -> String {
+        let object = JsonObject::new()
+            .string("session", session_id)
+            .integer("run", run)
+            .string("level", level_id)
+            .number("time", time);
+
+        match self {
+            Self::RunStarted { seed, .. } => {
+                object.string("event", "run_start").integer("seed", *seed)
+            }
+            Self::RunEnded { completed, stats } => object
+                .string("event", "run_end")
+                .boolean("completed", *completed)
+                .integer("score", stats.score)
+                .integer("squashed_creeps", stats.squashed_creeps)
+                .integer("max_combo", stats.max_combo)
+                .number("time_survived", stats.time_survived),
+            Self::Squash {
+                position,
+                combo,
+                score,
+            } => object
+                .string("event", "squash")
+                .vector("position", *position)
+                .integer("combo", *combo)
+                .integer("score", *score),
+            Self::Death { position, cause } => object
+                .string("event", "death")
+                .vector("position", *position)
+                .string("cause", cause.id()),
+            Self::PowerUpGained { kind } => object
+                .string("event", "power_up_gained")
+                .string("kind", kind),
+            Self::PowerUpExpired { kind } => object
+                .string("event", "power_up_expired")
+                .string("kind", kind),
+            Self::LevelComplete { time, score } => object
+                .string("event", "level_complete")
+                .number("level_time", *time)
+                .integer("score", *score),
+        }
+        .finish()
+    }
+}
+
+/// Id of the session: start time and random part, so sessions of
+/// different players never have equal ids.
+fn session_id() -> &'static str {
+    SESSION_ID.get_or_init(|| {
+        // own generator, global one is seeded for replays
+        let mut rng = RandomNumberGenerator::new_gd();
+        rng.randomize();
+        format!(
+            "{}-{:08x}",
+            Time::singleton().get_unix_time_from_system() as u64,
+            rng.randi()
+        )
+    })
+}
+
+/// This struct write events of the scene to the log of the session.
+#[derive(Default)]
+pub struct EventLog {
+    /// Log file, opened on the first event.
+    file: Option<Gd<FileAccess>>,
+
+    /// Number of the current run in the session.
+    run: u32,
+
+    /// Id of the level of the current run.
+    level_id: String,
+
+    /// Time of the start of the run, in msecs since start of the engine.
+    run_start: u64,
+
+    /// Events are not written (automated runs of bot and tests).
+    disabled: bool,
+}
+
+impl EventLog {
+    /// Write event to the log, 'RunStarted' starts a new run.
+    pub fn log(&mut self, event: GameEvent) {
+        if self.disabled {
+            return;
+        }
+
+        let now = Time::singleton().get_ticks_msec();
+        if let GameEvent::RunStarted { level_id, .. } = &event {
+            self.run = LAST_RUN.fetch_add(1, Ordering::Relaxed) + 1;
+            self.level_id = level_id.clone();
+            self.run_start = now;
+        }
+
+        let time = now.saturating_sub(self.run_start) as f64 / 1000.0;
+        let line = event.to_json(session_id(), self.run, &self.level_id, time);
+        if let Some(file) = self.file() {
+            file.store_line(&line);
+            file.flush();
+        }
+    }
+
+    /// Stop writing events, the log file is not created.
+    pub fn disable(&mut self) {
+        self.disabled = true;
+    }
+
+    /// Log file of the session, it is opened for append.
+    fn file(&mut self) -> Option<&mut Gd<FileAccess>> {
+        if self.file.is_none() {
+            let error = DirAccess::make_dir_recursive_absolute(LOG_DIRECTORY);
+            if error != Error::OK {
+                godot_warn!("failed to create '{LOG_DIRECTORY}': {error:?}");
+            }
+
+            // other scenes of the session write to the same file
+            let path = format!("{LOG_DIRECTORY}/{}.{LOG_EXTENSION}", session_id());
+            let file = FileAccess::open(&path, ModeFlags::READ_WRITE)
+                .or_else(|| FileAccess::open(&path, ModeFlags::WRITE));
+            match file {
+                Some(mut file) => {
+                    file.seek_end();
+                    self.file = Some(file);
+                }
+                None => godot_error!("failed to open log '{path}'"),
+            }
+        }
+
+        self.file.as_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn squash_to_json() {
+        let event = GameEvent::Squash {
+            position: Vector3::new(1.0, 0.5, -2.25),
+            combo: 3,
+            score: 2,
+        };
+        assert_eq!(
+            event.to_json("s1", 2, "arena", 12.347),
+            r#"{"session":"s1","run":2,"level":"arena","time":12.35,"event":"squash","position":[1,0.5,-2.25],"combo":3,"score":2}"#
+        );
+    }
+
+    #[test]
+    fn strings_are_escaped() {
+        let event = GameEvent::RunStarted {
+            level_id: "a\"b".to_string(),
+            seed: 7,
+        };
+        assert_eq!(
+            event.to_json("s1", 1, "a\"b\\c\n", 0.0),
+            r#"{"session":"s1","run":1,"level":"a\"b\\c\u000a","time":0,"event":"run_start","seed":7}"#
+        );
+    }
+
+    #[test]
+    fn death_cause_and_infinite_number() {
+        let event = GameEvent::Death {
+            position: Vector3::new(f32::INFINITY, 0.0, 0.0),
+            cause: DeathCause::Fall,
+        };
+        let json = event.to_json("s1", 1, "level_1", 1.0);
+        assert!(json.ends_with(r#""event":"death","position":[null,0,0],"cause":"fall"}"#));
+    }
+}
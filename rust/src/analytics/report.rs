@@ -0,0 +1,370 @@
+//! This module store a report tool for event logs. Report aggregates logs
+//! of all sessions into heatmaps of deaths and squashes for every level,
+//! heatmap is a grid of cells on the floor (axes X and Z) with amount of
+//! events in every cell.
+//!
+//! For every level the tool writes to the output directory (by default
+//! 'user://reports') images '<level>_deaths.png' and '<level>_squashes.png'
+//! (cells of both images are equal, lighter cell has more events) and
+//! '<level>.csv' with counts of every cell:
+//! ```sh
+//! godot --headless --path godot res://scenes/tools/event_report.tscn
+//! godot --headless --path godot res://scenes/tools/event_report.tscn -- --cell=1 --output=user://reports user://logs/1760870400-3fa2c81d.jsonl
+//! ```
+
+use super::{LOG_DIRECTORY, LOG_EXTENSION};
+use crate::command_line::{argument, user_arguments};
+use godot::{
+    classes::{DirAccess, FileAccess, INode, Image, Json, file_access::ModeFlags, image::Format},
+    global::Error,
+    obj::WithBaseField,
+    prelude::*,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Default directory for reports.
+const REPORT_DIRECTORY: &str = "user://reports";
+
+/// Default size of a heatmap cell, in m.
+const DEFAULT_CELL_SIZE: f32 = 2.0;
+
+/// Size of a heatmap cell on the image, in px.
+const CELL_PIXELS: i32 = 8;
+
+/// Color of cells without events.
+const EMPTY_COLOR: Color = Color::from_rgb(0.08, 0.08, 0.12);
+
+/// Color of the cell with most events.
+const HOT_COLOR: Color = Color::from_rgb(1.0, 0.9, 0.3);
+
+/// This struct store amount of events in cells of the floor.
+#[derive(Default)]
+pub struct Heatmap {
+    /// Amount of events by cell (x, z).
+    cells: HashMap<(i32, i32), u32>,
+}
+
+impl Heatmap {
+    /// Add event at position.
+    pub fn add(&mut self, position: Vector3, cell_size: f32) {
+        let cell = (
+            (position.x / cell_size).floor() as i32,
+            (position.z / cell_size).floor() as i32,
+        );
+        *self.cells.entry(cell).or_default() += 1;
+    }
+
+    /// Amount of events in cell.
+    pub fn get(&self, cell: (i32, i32)) -> u32 {
+        self.cells.get(&cell).copied().unwrap_or_default()
+    }
+
+    /// Biggest amount of events in a cell.
+    pub fn max(&self) -> u32 {
+        self.cells.values().copied().max().unwrap_or_default()
+    }
+
+    /// Amount of all events.
+    pub fn total(&self) -> u32 {
+        self.cells.values().sum()
+    }
+}
+
+/// Smallest and biggest cell of heatmaps, 'None' if heatmaps are empty.
+pub fn bounds<'a>(
+    heatmaps: impl IntoIterator<Item = &'a Heatmap>,
+) -> Option<((i32, i32), (i32, i32))> {
+    heatmaps
+        .into_iter()
+        .flat_map(|heatmap| heatmap.cells.keys())
+        .fold(None, |bounds, &(x, z)| match bounds {
+            None => Some(((x, z), (x, z))),
+            Some(((min_x, min_z), (max_x, max_z))) => {
+                Some(((min_x.min(x), min_z.min(z)), (max_x.max(x), max_z.max(z))))
+            }
+        })
+}
+
+/// This struct store aggregated events of one level.
+#[derive(Default)]
+struct LevelReport {
+    /// Amount of started runs.
+    runs: u32,
+
+    /// Amount of completes of the level.
+    completes: u32,
+
+    /// Amount of deaths by cause id.
+    death_causes: BTreeMap<String, u32>,
+
+    /// Positions of deaths.
+    deaths: Heatmap,
+
+    /// Positions of squashes.
+    squashes: Heatmap,
+}
+
+impl LevelReport {
+    /// Add event from the log.
+    fn add(&mut self, event: &str, line: &Dictionary, cell_size: f32) {
+        match event {
+            "run_start" => self.runs += 1,
+            "level_complete" => self.completes += 1,
+            "death" => {
+                let cause = line
+                    .get("cause")
+                    .and_then(|cause| cause.try_to::<GString>().ok())
+                    .map_or("unknown".to_string(), |cause| cause.to_string());
+                *self.death_causes.entry(cause).or_default() += 1;
+                if let Some(position) = position(line) {
+                    self.deaths.add(position, cell_size);
+                }
+            }
+            "squash" => {
+                if let Some(position) = position(line) {
+                    self.squashes.add(position, cell_size);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Position of event, 'None' if line has no valid position.
+fn position(line: &Dictionary) -> Option<Vector3> {
+    let axes: VariantArray = line.get("position")?.try_to().ok()?;
+    if axes.len() != 3 {
+        return None;
+    }
+
+    let axis = |index| {
+        axes.at(index)
+            .try_to::<f64>()
+            .ok()
+            .map(|value| value as f32)
+    };
+    Some(Vector3::new(axis(0)?, axis(1)?, axis(2)?))
+}
+
+/// All log files in the log directory.
+fn all_logs() -> Vec<String> {
+    DirAccess::get_files_at(LOG_DIRECTORY)
+        .as_slice()
+        .iter()
+        .map(|file| file.to_string())
+        .filter(|file| file.ends_with(&format!(".{LOG_EXTENSION}")))
+        .map(|file| format!("{LOG_DIRECTORY}/{file}"))
+        .collect()
+}
+
+/// Write image of heatmap, cells in bounds are drawn from light (many events) to dark.
+fn write_image(
+    heatmap: &Heatmap,
+    ((min_x, min_z), (max_x, max_z)): ((i32, i32), (i32, i32)),
+    path: &str,
+) -> Result<(), String> {
+    let width = (max_x - min_x + 1) * CELL_PIXELS;
+    let height = (max_z - min_z + 1) * CELL_PIXELS;
+    let mut image = Image::create_empty(width, height, false, Format::RGBA8)
+        .ok_or_else(|| format!("failed to create image {width}x{height}"))?;
+    image.fill(EMPTY_COLOR);
+
+    let max = heatmap.max().max(1) as f32;
+    for x in min_x..=max_x {
+        for z in min_z..=max_z {
+            let count = heatmap.get((x, z));
+            if count == 0 {
+                continue;
+            }
+
+            let color = EMPTY_COLOR.lerp(HOT_COLOR, (count as f32 / max).sqrt() as f64);
+            for pixel_x in 0..CELL_PIXELS {
+                for pixel_y in 0..CELL_PIXELS {
+                    image.set_pixel(
+                        (x - min_x) * CELL_PIXELS + pixel_x,
+                        (z - min_z) * CELL_PIXELS + pixel_y,
+                        color,
+                    );
+                }
+            }
+        }
+    }
+
+    let error = image.save_png(path);
+    if error != Error::OK {
+        return Err(format!("failed to write '{path}': {error:?}"));
+    }
+
+    Ok(())
+}
+
+/// Write counts of all cells with events to CSV.
+fn write_csv(report: &LevelReport, cell_size: f32, path: &str) -> Result<(), String> {
+    let mut file = FileAccess::open(path, ModeFlags::WRITE)
+        .ok_or_else(|| format!("failed to write '{path}'"))?;
+    file.store_line("cell_x,cell_z,x,z,deaths,squashes");
+
+    let mut cells: Vec<(i32, i32)> = report
+        .deaths
+        .cells
+        .keys()
+        .chain(report.squashes.cells.keys())
+        .copied()
+        .collect();
+    cells.sort_unstable();
+    cells.dedup();
+
+    for (x, z) in cells {
+        file.store_line(&format!(
+            "{x},{z},{},{},{},{}",
+            x as f32 * cell_size,
+            z as f32 * cell_size,
+            report.deaths.get((x, z)),
+            report.squashes.get((x, z))
+        ));
+    }
+    file.close();
+
+    Ok(())
+}
+
+/// This class aggregate event logs to heatmaps and quit.
+#[derive(GodotClass)]
+#[class(base = Node)]
+pub struct EventReportRunner {
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for EventReportRunner {
+    fn init(base: Base<Node>) -> Self {
+        Self { base }
+    }
+
+    fn ready(&mut self) {
+        let arguments = user_arguments();
+
+        let cell_size = argument(&arguments, "cell")
+            .and_then(|cell| cell.parse().ok())
+            .filter(|&cell: &f32| cell > 0.0)
+            .unwrap_or(DEFAULT_CELL_SIZE);
+        let output = argument(&arguments, "output").unwrap_or(REPORT_DIRECTORY.to_string());
+
+        // logs from command line, or all logs
+        let mut logs: Vec<String> = arguments
+            .iter()
+            .filter(|argument| !argument.starts_with("--"))
+            .cloned()
+            .collect();
+        if logs.is_empty() {
+            logs = all_logs();
+        }
+
+        // aggregate events by level
+        let mut reports: BTreeMap<String, LevelReport> = BTreeMap::new();
+        let mut invalid_lines = 0;
+        for path in &logs {
+            let text = FileAccess::get_file_as_string(path).to_string();
+            for line in text.lines().filter(|line| !line.trim().is_empty()) {
+                let Ok(line) = Json::parse_string(line).try_to::<Dictionary>() else {
+                    invalid_lines += 1;
+                    continue;
+                };
+                let field = |name| {
+                    line.get(name)
+                        .and_then(|value| value.try_to::<GString>().ok())
+                        .map(|value| value.to_string())
+                };
+                let (Some(level_id), Some(event)) = (field("level"), field("event")) else {
+                    invalid_lines += 1;
+                    continue;
+                };
+
+                reports
+                    .entry(level_id)
+                    .or_default()
+                    .add(&event, &line, cell_size);
+            }
+        }
+
+        godot_print!(
+            "{} logs read, {invalid_lines} invalid lines, {} levels",
+            logs.len(),
+            reports.len()
+        );
+
+        // write heatmaps of every level
+        let error = DirAccess::make_dir_recursive_absolute(&output);
+        if error != Error::OK {
+            godot_warn!("failed to create '{output}': {error:?}");
+        }
+
+        let mut failed = false;
+        for (level_id, report) in &reports {
+            let causes: Vec<String> = report
+                .death_causes
+                .iter()
+                .map(|(cause, count)| format!("{cause} {count}"))
+                .collect();
+            godot_print!(
+                "{level_id}: {} runs, {} completes, {} squashes, {} deaths ({})",
+                report.runs,
+                report.completes,
+                report.squashes.total(),
+                report.deaths.total(),
+                causes.join(", ")
+            );
+
+            let mut result = write_csv(report, cell_size, &format!("{output}/{level_id}.csv"));
+            if let Some(bounds) = bounds([&report.deaths, &report.squashes]) {
+                let heatmaps = [("deaths", &report.deaths), ("squashes", &report.squashes)];
+                for (name, heatmap) in heatmaps {
+                    let path = format!("{output}/{level_id}_{name}.png");
+                    result = result.and_then(|_| write_image(heatmap, bounds, &path));
+                }
+            }
+
+            if let Err(error) = result {
+                godot_error!("{error}");
+                failed = true;
+            }
+        }
+
+        let exit_code = if failed { 1 } else { 0 };
+        self.base()
+            .get_tree()
+            .unwrap()
+            .quit_ex()
+            .exit_code(exit_code)
+            .done();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_counted_by_cell() {
+        let mut heatmap = Heatmap::default();
+        heatmap.add(Vector3::new(0.5, 3.0, 0.5), 2.0);
+        heatmap.add(Vector3::new(1.9, 0.0, 1.0), 2.0);
+        heatmap.add(Vector3::new(-0.1, 0.0, 4.0), 2.0);
+
+        assert_eq!(heatmap.get((0, 0)), 2);
+        assert_eq!(heatmap.get((-1, 2)), 1);
+        assert_eq!(heatmap.max(), 2);
+        assert_eq!(heatmap.total(), 3);
+    }
+
+    #[test]
+    fn bounds_cover_all_heatmaps() {
+        let mut deaths = Heatmap::default();
+        deaths.add(Vector3::new(-5.0, 0.0, 1.0), 1.0);
+        let mut squashes = Heatmap::default();
+        squashes.add(Vector3::new(3.0, 0.0, -2.0), 1.0);
+
+        assert_eq!(bounds([&deaths, &squashes]), Some(((-5, -2), (3, 1))));
+        assert_eq!(bounds([&Heatmap::default()]), None);
+    }
+}
@@ -0,0 +1,564 @@
+//! This module store a generator of procedural arenas for Main scene.
+//! Generator place obstacles and platforms from a seed, check that
+//! all of them can be reached by player and build a new spawn path
+//! for creeps around the generated layout.
+
+use crate::{
+    levels::{
+        loader::{BoxDescription, create_box},
+        reach::{JumpAbility, Surface, reachable_surfaces},
+    },
+    player::Player,
+};
+use godot::{
+    classes::{
+        CollisionShape3D, Curve3D, INode3D, Material, RandomNumberGenerator, VisualInstance3D,
+    },
+    global::randi,
+    obj::WithBaseField,
+    prelude::*,
+};
+use std::collections::VecDeque;
+
+/// How many times generator try to place one block.
+const PLACE_ATTEMPTS: usize = 20;
+
+/// Size of cell (in m) for check that ground is connected.
+const GRID_CELL_SIZE: f32 = 1.0;
+
+/// Radius of the player (in m), obstacles are inflated by it.
+const PLAYER_RADIUS: f32 = 0.6;
+
+/// Height of the spawn path above the ground.
+const SPAWN_PATH_HEIGHT: f32 = 1.0;
+
+/// This enum store all kinds of blocks in arena.
+#[derive(Clone, Copy, PartialEq)]
+pub enum BlockKind {
+    /// High block, player can't jump on it.
+    Obstacle,
+
+    /// Low block, player can jump on it.
+    Platform,
+}
+
+/// This struct describe one block of arena.
+#[derive(Clone, Copy)]
+pub struct ArenaBlock {
+    pub kind: BlockKind,
+
+    /// Center of the block bottom.
+    pub position: Vector3,
+
+    /// Size of the block.
+    pub size: Vector3,
+}
+
+impl ArenaBlock {
+    /// Obstacle which covers box transformed to the world.
+    pub fn from_aabb(transform: Transform3D, aabb: Aabb) -> Self {
+        let mut min = Vector3::INF;
+        let mut max = -Vector3::INF;
+        for i in 0..8 {
+            let corner = aabb.position
+                + aabb.size
+                    * Vector3::new((i & 1) as f32, ((i >> 1) & 1) as f32, ((i >> 2) & 1) as f32);
+            let corner = transform * corner;
+            min = min.coord_min(corner);
+            max = max.coord_max(corner);
+        }
+
+        Self {
+            kind: BlockKind::Obstacle,
+            position: Vector3::new((min.x + max.x) / 2.0, min.y, (min.z + max.z) / 2.0),
+            size: max - min,
+        }
+    }
+
+    /// Minimal corner of block footprint inflated by margin.
+    fn min(&self, margin: f32) -> Vector2 {
+        Vector2::new(
+            self.position.x - self.size.x / 2.0 - margin,
+            self.position.z - self.size.z / 2.0 - margin,
+        )
+    }
+
+    /// Maximal corner of block footprint inflated by margin.
+    fn max(&self, margin: f32) -> Vector2 {
+        Vector2::new(
+            self.position.x + self.size.x / 2.0 + margin,
+            self.position.z + self.size.z / 2.0 + margin,
+        )
+    }
+
+    /// Height of top of the block.
+    fn top(&self) -> f32 {
+        self.position.y + self.size.y
+    }
+
+    /// Return true if footprints of blocks (inflated by margin) intersect.
+    fn intersects(&self, other: &ArenaBlock, margin: f32) -> bool {
+        let (min, max) = (self.min(margin), self.max(margin));
+        let (other_min, other_max) = (other.min(0.0), other.max(0.0));
+
+        min.x < other_max.x && max.x > other_min.x && min.y < other_max.y && max.y > other_min.y
+    }
+
+    /// Return true if point (x and z) is inside of block footprint inflated by margin.
+    fn contains(&self, point: Vector2, margin: f32) -> bool {
+        let (min, max) = (self.min(margin), self.max(margin));
+        point.x >= min.x && point.x <= max.x && point.y >= min.y && point.y <= max.y
+    }
+
+    /// Top surface of the block.
+    fn surface(&self) -> Surface {
+        Surface {
+            min: self.min(0.0),
+            max: self.max(0.0),
+            height: self.top(),
+        }
+    }
+}
+
+/// This struct store parameters of generator.
+pub struct ArenaParams {
+    /// Center of the arena on the ground.
+    pub center: Vector3,
+
+    /// Half of arena size (x and z).
+    pub half_size: Vector2,
+
+    /// How much obstacles generator try to place.
+    pub obstacle_count: i64,
+
+    /// How much platforms generator try to place.
+    pub platform_count: i64,
+
+    /// Point where player spawn, it is kept free.
+    pub safe_point: Vector3,
+
+    /// Radius around safe point without blocks.
+    pub safe_radius: f32,
+
+    /// Distance from arena edge to spawn path of creeps.
+    pub spawn_margin: f32,
+
+    /// How player can jump.
+    pub jump: JumpAbility,
+
+    /// Static geometry of the scene, generated blocks must not overlap it.
+    pub static_blocks: Vec<ArenaBlock>,
+}
+
+/// This struct store generated arena.
+pub struct ArenaLayout {
+    /// All blocks of arena.
+    pub blocks: Vec<ArenaBlock>,
+
+    /// Points of closed spawn path around the arena.
+    pub spawn_path: Vec<Vector3>,
+}
+
+/// Return true if all free ground cells are connected with safe point.
+fn is_ground_connected(params: &ArenaParams, blocks: &[ArenaBlock]) -> bool {
+    let width = (params.half_size.x * 2.0 / GRID_CELL_SIZE) as usize;
+    let height = (params.half_size.y * 2.0 / GRID_CELL_SIZE) as usize;
+    let origin = Vector2::new(
+        params.center.x - params.half_size.x,
+        params.center.z - params.half_size.y,
+    );
+
+    // mark cells blocked by obstacles and static geometry
+    let cell_center =
+        |x: usize, z: usize| origin + Vector2::new(x as f32 + 0.5, z as f32 + 0.5) * GRID_CELL_SIZE;
+    let mut free = vec![false; width * height];
+    for z in 0..height {
+        for x in 0..width {
+            free[z * width + x] = !params.static_blocks.iter().chain(blocks).any(|block| {
+                block.kind == BlockKind::Obstacle
+                    && block.contains(cell_center(x, z), PLAYER_RADIUS)
+            });
+        }
+    }
+
+    // fill from safe point
+    let start_x = ((params.safe_point.x - origin.x) / GRID_CELL_SIZE) as usize;
+    let start_z = ((params.safe_point.z - origin.y) / GRID_CELL_SIZE) as usize;
+    let start = start_z.min(height - 1) * width + start_x.min(width - 1);
+
+    let mut visited = vec![false; width * height];
+    let mut queue = VecDeque::from([start]);
+    visited[start] = true;
+
+    while let Some(cell) = queue.pop_front() {
+        let (x, z) = (cell % width, cell / width);
+        let neighbours = [
+            (x > 0).then(|| cell - 1),
+            (x + 1 < width).then(|| cell + 1),
+            (z > 0).then(|| cell - width),
+            (z + 1 < height).then(|| cell + width),
+        ];
+
+        for next in neighbours.into_iter().flatten() {
+            if free[next] && !visited[next] {
+                visited[next] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    free.iter()
+        .zip(&visited)
+        .all(|(free, visited)| !free || *visited)
+}
+
+/// Generate random footprint inside the arena.
+fn random_footprint(
+    rng: &mut Gd<RandomNumberGenerator>,
+    params: &ArenaParams,
+    min_size: f32,
+    max_size: f32,
+) -> (Vector3, Vector3) {
+    let size = Vector3::new(
+        rng.randf_range(min_size, max_size),
+        0.0,
+        rng.randf_range(min_size, max_size),
+    );
+
+    let position = Vector3::new(
+        params.center.x
+            + rng.randf_range(
+                -params.half_size.x + size.x / 2.0,
+                params.half_size.x - size.x / 2.0,
+            ),
+        params.center.y,
+        params.center.z
+            + rng.randf_range(
+                -params.half_size.y + size.z / 2.0,
+                params.half_size.y - size.z / 2.0,
+            ),
+    );
+
+    (position, size)
+}
+
+/// Return true if block can be placed to arena,
+/// block with base ('base' is index in blocks) is not checked against the base.
+fn can_place(
+    params: &ArenaParams,
+    blocks: &[ArenaBlock],
+    block: &ArenaBlock,
+    base: Option<usize>,
+) -> bool {
+    let safe_point = Vector2::new(params.safe_point.x, params.safe_point.z);
+    let others = blocks
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| Some(*i) != base)
+        .map(|(_, other)| other);
+
+    !block.contains(safe_point, params.safe_radius)
+        && !params
+            .static_blocks
+            .iter()
+            .chain(others)
+            .any(|other| block.intersects(other, PLAYER_RADIUS * 2.0))
+}
+
+/// Generate arena layout.
+pub fn generate(rng: &mut Gd<RandomNumberGenerator>, params: &ArenaParams) -> ArenaLayout {
+    let mut blocks: Vec<ArenaBlock> = Vec::new();
+
+    // place obstacles, they must not split the ground
+    for _ in 0..params.obstacle_count {
+        for _ in 0..PLACE_ATTEMPTS {
+            let (position, mut size) = random_footprint(rng, params, 1.0, 3.0);
+            size.y = rng.randf_range(params.jump.height * 1.5, params.jump.height * 2.5);
+            let block = ArenaBlock {
+                kind: BlockKind::Obstacle,
+                position,
+                size,
+            };
+
+            if !can_place(params, &blocks, &block, None) {
+                continue;
+            }
+
+            blocks.push(block);
+            if is_ground_connected(params, &blocks) {
+                break;
+            }
+            blocks.pop();
+        }
+    }
+
+    // place platforms, some of them on top of other platforms
+    for _ in 0..params.platform_count {
+        for _ in 0..PLACE_ATTEMPTS {
+            let (mut position, mut size) = random_footprint(rng, params, 3.0, 6.0);
+            size.y = rng.randf_range(params.jump.height * 0.3, params.jump.height * 0.8);
+
+            // try to stack platform on top of other platform
+            let base = blocks.iter().position(|block| {
+                block.kind == BlockKind::Platform
+                    && block.contains(Vector2::new(position.x, position.z), 0.0)
+            });
+
+            let block = match base {
+                Some(index) => {
+                    let base = blocks[index];
+
+                    size.x = size.x.min(base.size.x * 0.7);
+                    size.z = size.z.min(base.size.z * 0.7);
+
+                    // keep stacked platform inside of base platform
+                    let (min, max) = (base.min(0.0), base.max(0.0));
+                    position.x = position.x.clamp(min.x + size.x / 2.0, max.x - size.x / 2.0);
+                    position.z = position.z.clamp(min.y + size.z / 2.0, max.y - size.z / 2.0);
+                    position.y = base.top();
+
+                    ArenaBlock {
+                        kind: BlockKind::Platform,
+                        position,
+                        size,
+                    }
+                }
+                None => ArenaBlock {
+                    kind: BlockKind::Platform,
+                    position,
+                    size,
+                },
+            };
+
+            if !can_place(params, &blocks, &block, base) {
+                continue;
+            }
+
+            blocks.push(block);
+            break;
+        }
+    }
+
+    // remove platforms which player can't reach from the ground
+    let ground = Surface {
+        min: Vector2::new(
+            params.center.x - params.half_size.x,
+            params.center.z - params.half_size.y,
+        ),
+        max: Vector2::new(
+            params.center.x + params.half_size.x,
+            params.center.z + params.half_size.y,
+        ),
+        height: params.center.y,
+    };
+    let surfaces: Vec<Surface> = std::iter::once(ground)
+        .chain(blocks.iter().map(ArenaBlock::surface))
+        .collect();
+    let reachable = reachable_surfaces(&surfaces, 0, params.jump);
+
+    let blocks: Vec<ArenaBlock> = blocks
+        .into_iter()
+        .zip(reachable.into_iter().skip(1))
+        .filter(|(block, reachable)| block.kind == BlockKind::Obstacle || *reachable)
+        .map(|(block, _)| block)
+        .collect();
+
+    // build spawn path around arena
+    let half = params.half_size + Vector2::new(params.spawn_margin, params.spawn_margin);
+    let y = params.center.y + SPAWN_PATH_HEIGHT;
+    let spawn_path = vec![
+        Vector3::new(params.center.x + half.x, y, params.center.z - half.y),
+        Vector3::new(params.center.x + half.x, y, params.center.z + half.y),
+        Vector3::new(params.center.x - half.x, y, params.center.z + half.y),
+        Vector3::new(params.center.x - half.x, y, params.center.z - half.y),
+    ];
+
+    ArenaLayout { blocks, spawn_path }
+}
+
+/// This class build procedural arena in Main scene.
+#[derive(GodotClass)]
+#[class(base = Node3D)]
+pub struct ProceduralArena {
+    /// Generate new arena for every run.
+    #[export]
+    pub enabled: bool,
+
+    /// Seed of arena, if 0 then random seed is used for every run.
+    #[export]
+    pub seed: i64,
+
+    /// Center of the arena on the ground.
+    #[export]
+    center: Vector3,
+
+    /// Half of arena size (x and z).
+    #[export]
+    half_size: Vector2,
+
+    /// How much obstacles generator try to place.
+    #[export]
+    obstacle_count: i64,
+
+    /// How much platforms generator try to place.
+    #[export]
+    platform_count: i64,
+
+    /// Radius around player spawn without blocks.
+    #[export]
+    safe_radius: f32,
+
+    /// Distance from arena edge to spawn path of creeps.
+    #[export]
+    spawn_margin: f32,
+
+    /// Nodes with static geometry (walls, decorations), blocks are not placed over them.
+    #[export]
+    static_geometry: Array<NodePath>,
+
+    /// Seed of the last generated arena.
+    pub last_seed: i64,
+
+    base: Base<Node3D>,
+}
+
+#[godot_api]
+impl INode3D for ProceduralArena {
+    fn init(base: Base<Node3D>) -> Self {
+        Self {
+            enabled: false,
+            seed: 0,
+            center: Vector3::new(0.5, 0.0, 1.5),
+            half_size: Vector2::new(12.5, 13.5),
+            obstacle_count: 6,
+            platform_count: 5,
+            safe_radius: 3.0,
+            spawn_margin: 9.0,
+            static_geometry: Array::new(),
+            last_seed: 0,
+            base
+        }
+    }
+}
+
+#[godot_api]
+impl ProceduralArena {
+    /// Remove old arena and generate a new one.
+    /// Return points of spawn path for creeps.
+    pub fn rebuild(&mut self, player: &Player) -> Vec<Vector3> {
+        // remove old blocks, they are removed from tree at once so new blocks keep their names
+        let children = self.base().get_children();
+        for mut child in children.iter_shared() {
+            self.base_mut().remove_child(&child);
+            child.queue_free();
+        }
+
+        // collect static geometry of the scene
+        let mut static_blocks = Vec::new();
+        for path in self.static_geometry.iter_shared() {
+            match self.base().get_node_or_null(&path) {
+                Some(node) => collect_static_blocks(&node, &mut static_blocks),
+                None => godot_warn!("static geometry '{path}' not found"),
+            }
+        }
+
+        // get seed
+        self.last_seed = if self.seed != 0 { self.seed } else { randi() };
+
+        let mut rng = RandomNumberGenerator::new_gd();
+        rng.set_seed(self.last_seed as u64);
+
+        // generate layout
+        let params = ArenaParams {
+            center: self.center,
+            half_size: self.half_size,
+            obstacle_count: self.obstacle_count,
+            platform_count: self.platform_count,
+            safe_point: player.spawn_coords,
+            safe_radius: self.safe_radius,
+            spawn_margin: self.spawn_margin,
+            jump: JumpAbility::new(player.jump_impulse, player.fall_acceleration, player.speed),
+            static_blocks,
+        };
+        let layout = generate(&mut rng, &params);
+
+        // create blocks
+        let obstacle_material = try_load::<Material>("res://art/materials/wall.tres").ok();
+        let platform_material = try_load::<Material>("res://art/materials/platform.tres").ok();
+
+        for (i, block) in layout.blocks.iter().enumerate() {
+            let description = BoxDescription {
+                position: block.position + Vector3::new(0.0, block.size.y / 2.0, 0.0),
+                size: block.size,
+                rotation: 0.0,
+            };
+
+            let body = match block.kind {
+                BlockKind::Obstacle => {
+                    create_box(&format!("Obstacle{i}"), &description, &obstacle_material)
+                }
+                BlockKind::Platform => {
+                    create_box(&format!("Platform{i}"), &description, &platform_material)
+                }
+            };
+            self.base_mut().add_child(&body);
+        }
+
+        godot_print!("arena generated with seed {}", self.last_seed);
+
+        layout.spawn_path
+    }
+}
+
+/// Collect footprints of collision shapes and meshes of node and all his descendants.
+fn collect_static_blocks(node: &Gd<Node>, blocks: &mut Vec<ArenaBlock>) {
+    if let Ok(collision) = node.clone().try_cast::<CollisionShape3D>() {
+        let mesh = collision
+            .get_shape()
+            .and_then(|shape| shape.get_debug_mesh());
+        if let Some(mesh) = mesh {
+            blocks.push(ArenaBlock::from_aabb(
+                collision.get_global_transform(),
+                mesh.get_aabb(),
+            ));
+        }
+    } else if let Ok(visual) = node.clone().try_cast::<VisualInstance3D>() {
+        blocks.push(ArenaBlock::from_aabb(
+            visual.get_global_transform(),
+            visual.get_aabb(),
+        ));
+    }
+
+    for child in node.get_children().iter_shared() {
+        collect_static_blocks(&child, blocks);
+    }
+}
+
+/// Create closed curve from points.
+pub fn closed_curve(points: &[Vector3]) -> Gd<Curve3D> {
+    let mut curve = Curve3D::new_gd();
+    for point in points {
+        curve.add_point(*point);
+    }
+    curve.set_closed(true);
+
+    curve
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotated_box_covers_turned_footprint() {
+        let aabb = Aabb::new(Vector3::new(-2.0, -1.0, -0.5), Vector3::new(4.0, 2.0, 1.0));
+        let transform = Transform3D::new(
+            Basis::from_axis_angle(Vector3::UP, std::f32::consts::FRAC_PI_2),
+            Vector3::new(10.0, 3.0, 0.0),
+        );
+        let block = ArenaBlock::from_aabb(transform, aabb);
+
+        assert!(block.position.is_equal_approx(Vector3::new(10.0, 2.0, 0.0)));
+        assert!(block.size.is_equal_approx(Vector3::new(1.0, 2.0, 4.0)));
+    }
+}
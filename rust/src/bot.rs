@@ -0,0 +1,427 @@
+//! This module store a bot which plays instead of the gamer, and a batch
+//! mode for balance testing. Bot drives the 'Player' with the same input
+//! actions as the gamer: chases the nearest creep, jumps when the creep is
+//! close to land on him and evades creeps which come from the side.
+//!
+//! Batch mode plays N headless games with different seeds and writes score
+//! and survival time of every game to CSV:
+//! ```sh
+//! godot --headless --fixed-fps 60 --path godot res://scenes/tools/bot_batch.tscn -- --games=100 --seed=1 --output=user://balance.csv
+//! ```
+
+use crate::{
+    command_line::{argument, user_arguments},
+    harness::{MAIN_SCENE, TICKS_PER_SECOND},
+    main_scene::MainScene,
+    mob::{MOB_GROUP, Mob},
+    player::{DeathCause, Player},
+    replay::InputFrame,
+    spawn_director::SpawnDirector,
+};
+use godot::{
+    classes::{Engine, FileAccess, INode, file_access::ModeFlags},
+    obj::WithBaseField,
+    prelude::*,
+};
+
+/// Minimal part of direction which presses the move action.
+const ACTION_THRESHOLD: f32 = 0.38;
+
+/// This struct store what the bot knows about a creep.
+#[derive(Clone, Copy)]
+pub struct SeenMob {
+    /// Global position of the creep.
+    pub position: Vector3,
+
+    /// Velocity of the creep, in m/s.
+    pub velocity: Vector3,
+}
+
+/// This struct is a bot, he decides which actions are pressed every physics tick.
+#[derive(Clone)]
+pub struct Bot {
+    /// Horizontal distance to the creep at which bot jumps to land on him, in m.
+    pub jump_distance: f32,
+
+    /// Horizontal distance at which approaching creep is evaded, in m.
+    pub evade_distance: f32,
+}
+
+impl Default for Bot {
+    fn default() -> Self {
+        Self {
+            jump_distance: 2.5,
+            evade_distance: 3.0,
+        }
+    }
+}
+
+impl Bot {
+    /// Decide input for current tick by position of the player and seen creeps.
+    pub fn decide(&self, position: Vector3, on_floor: bool, mobs: &[SeenMob]) -> InputFrame {
+        // horizontal vector and distance from the player to creep
+        let to_mob = |mob: &SeenMob| {
+            let offset = mob.position - position;
+            Vector3::new(offset.x, 0.0, offset.z)
+        };
+
+        let Some(nearest) = mobs
+            .iter()
+            .min_by(|a, b| to_mob(a).length().total_cmp(&to_mob(b).length()))
+        else {
+            return InputFrame::default();
+        };
+
+        // in the air the bot steers to the nearest creep to land on him
+        let offset = to_mob(nearest);
+        if !on_floor {
+            return direction_to_frame(offset);
+        }
+
+        // other creep comes from the side, step away from his line of movement
+        let threat = mobs.iter().find(|mob| {
+            let offset = to_mob(mob);
+            !std::ptr::eq(*mob, nearest)
+                && offset.length() < self.evade_distance
+                && mob.velocity.dot(-offset) > 0.0
+        });
+        if let Some(threat) = threat {
+            let offset = to_mob(threat);
+            let side = Vector3::new(-threat.velocity.z, 0.0, threat.velocity.x);
+            let side = if side.dot(offset) > 0.0 { -side } else { side };
+            return direction_to_frame(side.normalized_or_zero() - offset.normalized_or_zero());
+        }
+
+        // chase the nearest creep and jump when he is close
+        let mut frame = direction_to_frame(offset);
+        if offset.length() < self.jump_distance {
+            frame = frame.with(InputFrame::JUMP);
+        }
+        frame
+    }
+
+    /// Decide input for the player, creeps are found in the scene tree.
+    pub fn next_frame(&self, player: &Player) -> InputFrame {
+        let player = player.base();
+        let Some(tree) = player.get_tree() else {
+            return InputFrame::default();
+        };
+
+        let mobs: Vec<SeenMob> = tree
+            .get_nodes_in_group(MOB_GROUP)
+            .iter_shared()
+            .filter_map(|mob| mob.try_cast::<Mob>().ok())
+            .filter(|mob| mob.is_visible() && mob.bind().is_alive())
+            .map(|mob| SeenMob {
+                position: mob.get_global_position(),
+                velocity: mob.get_velocity(),
+            })
+            .collect();
+
+        self.decide(player.get_global_position(), player.is_on_floor(), &mobs)
+    }
+}
+
+/// Convert horizontal direction to pressed move actions.
+fn direction_to_frame(direction: Vector3) -> InputFrame {
+    let direction = direction.normalized_or_zero();
+    let mut frame = InputFrame::default();
+    if direction.x > ACTION_THRESHOLD {
+        frame = frame.with(InputFrame::MOVE_RIGHT);
+    }
+    if direction.x < -ACTION_THRESHOLD {
+        frame = frame.with(InputFrame::MOVE_LEFT);
+    }
+    if direction.z > ACTION_THRESHOLD {
+        frame = frame.with(InputFrame::MOVE_BACK);
+    }
+    if direction.z < -ACTION_THRESHOLD {
+        frame = frame.with(InputFrame::MOVE_FORWARD);
+    }
+    frame
+}
+
+/// This struct store result of one bot game.
+struct GameResult {
+    seed: u64,
+    score: i64,
+    squashed_creeps: i64,
+    max_combo: i64,
+    time_survived: f64,
+    wave: i64,
+    death_cause: Option<DeathCause>,
+}
+
+/// Value at percentile (0..1) of sorted values.
+fn percentile(sorted: &[f64], percentile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+
+    let index = ((sorted.len() - 1) as f64 * percentile).round() as usize;
+    sorted[index]
+}
+
+/// This class play games by the bot and write results to CSV.
+#[derive(GodotClass)]
+#[class(base = Node)]
+pub struct BotBatchRunner {
+    /// Amount of games ('--games=N' in command line).
+    #[export]
+    games: i64,
+
+    /// Seed of the first game, next games use next seeds ('--seed=S').
+    #[export]
+    first_seed: i64,
+
+    /// Game stops after this time even if the bot is alive, in secs.
+    #[export]
+    max_game_time: f64,
+
+    /// Path to CSV file with results ('--output=path').
+    #[export]
+    output_path: GString,
+
+    /// Results of finished games.
+    results: Vec<GameResult>,
+
+    /// Scene of the running game.
+    scene: Option<Gd<Node>>,
+
+    /// Seed of the running game.
+    current_seed: u64,
+
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for BotBatchRunner {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            games: 20,
+            first_seed: 1,
+            max_game_time: 300.0,
+            output_path: "user://balance.csv".into(),
+            results: Vec::new(),
+            scene: None,
+            current_seed: 0,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        Engine::singleton().set_physics_ticks_per_second(TICKS_PER_SECOND);
+
+        // parameters from command line have priority
+        let arguments = user_arguments();
+
+        if let Some(games) = argument(&arguments, "games").and_then(|games| games.parse().ok()) {
+            self.games = games;
+        }
+        if let Some(seed) = argument(&arguments, "seed").and_then(|seed| seed.parse().ok()) {
+            self.first_seed = seed;
+        }
+        if let Some(path) = argument(&arguments, "output") {
+            self.output_path = path.into();
+        }
+
+        self.start_game();
+    }
+
+    fn physics_process(&mut self, _delta: f64) {
+        let Some(scene) = self.scene.clone() else {
+            return;
+        };
+
+        // game ends on death or after maximum time
+        let main_scene = scene.cast::<MainScene>();
+        let stats = main_scene.bind().stats().clone();
+        let is_alive = main_scene.get_node_as::<Player>("Player").bind().is_alive();
+        if is_alive && stats.time_survived < self.max_game_time {
+            return;
+        }
+
+        let wave = main_scene
+            .get_node_as::<SpawnDirector>("SpawnDirector")
+            .bind()
+            .get_wave_number();
+        let death_cause = if is_alive {
+            None
+        } else {
+            Some(
+                main_scene
+                    .get_node_as::<Player>("Player")
+                    .bind()
+                    .death_cause,
+            )
+        };
+
+        godot_print!(
+            "game {}/{}: seed {}, score {}, time {:.1}",
+            self.results.len() + 1,
+            self.games,
+            self.current_seed,
+            stats.score,
+            stats.time_survived
+        );
+        self.results.push(GameResult {
+            seed: self.current_seed,
+            score: stats.score,
+            squashed_creeps: stats.squashed_creeps,
+            max_combo: stats.max_combo,
+            time_survived: stats.time_survived,
+            wave,
+            death_cause,
+        });
+
+        // remove finished game and start next one
+        if let Some(mut scene) = self.scene.take() {
+            self.base_mut().remove_child(&scene);
+            scene.queue_free();
+        }
+        self.start_game();
+    }
+}
+
+#[godot_api]
+impl BotBatchRunner {
+    /// Boot arena and start a game played by the bot, or finish the batch.
+    fn start_game(&mut self) {
+        if self.results.len() as i64 >= self.games {
+            self.finish();
+            return;
+        }
+
+        let Some(scene) = try_load::<PackedScene>(MAIN_SCENE)
+            .ok()
+            .and_then(|scene| scene.instantiate())
+        else {
+            godot_error!("failed to load '{MAIN_SCENE}'");
+            self.quit(1);
+            return;
+        };
+
+        // games of the bot don't replace records and replays of the player
+        let mut main_scene = scene.clone().cast::<MainScene>();
+        main_scene.bind_mut().automated = true;
+        self.base_mut().add_child(&scene);
+
+        // seed of the run is passed to new game, so the arena, waves and creeps repeat
+        self.current_seed = (self.first_seed + self.results.len() as i64) as u64;
+        main_scene.bind_mut().new_seeded_game(self.current_seed);
+        main_scene.get_node_as::<Player>("Player").bind_mut().bot = Some(Bot::default());
+
+        self.scene = Some(scene);
+    }
+
+    /// Write results to CSV, print distribution and quit.
+    fn finish(&mut self) {
+        let path = self.output_path.to_string();
+        let Some(mut file) = FileAccess::open(&path, ModeFlags::WRITE) else {
+            godot_error!("failed to write '{path}'");
+            self.quit(1);
+            return;
+        };
+
+        file.store_line("seed,score,squashed_creeps,max_combo,time_survived,wave,death_cause");
+        for result in &self.results {
+            file.store_line(&format!(
+                "{},{},{},{},{:.3},{},{}",
+                result.seed,
+                result.score,
+                result.squashed_creeps,
+                result.max_combo,
+                result.time_survived,
+                result.wave,
+                result.death_cause.map_or("alive", DeathCause::id)
+            ));
+        }
+        file.close();
+
+        // short distribution for the log
+        for (name, mut values) in [
+            (
+                "score",
+                self.results
+                    .iter()
+                    .map(|result| result.score as f64)
+                    .collect::<Vec<_>>(),
+            ),
+            (
+                "time",
+                self.results
+                    .iter()
+                    .map(|result| result.time_survived)
+                    .collect(),
+            ),
+        ] {
+            values.sort_by(f64::total_cmp);
+            godot_print!(
+                "{name}: p10 {:.1}, median {:.1}, p90 {:.1}",
+                percentile(&values, 0.1),
+                percentile(&values, 0.5),
+                percentile(&values, 0.9)
+            );
+        }
+
+        godot_print!("{} games written to '{path}'", self.results.len());
+        self.quit(0);
+    }
+
+    /// Quit with exit code.
+    fn quit(&mut self, exit_code: i32) {
+        self.base()
+            .get_tree()
+            .unwrap()
+            .quit_ex()
+            .exit_code(exit_code)
+            .done();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mob(x: f32, z: f32, velocity: Vector3) -> SeenMob {
+        SeenMob {
+            position: Vector3::new(x, 0.0, z),
+            velocity,
+        }
+    }
+
+    #[test]
+    fn chases_nearest_creep() {
+        let mobs = [mob(10.0, 0.0, Vector3::ZERO), mob(0.0, -5.0, Vector3::ZERO)];
+        let frame = Bot::default().decide(Vector3::ZERO, true, &mobs);
+        assert_eq!(frame, InputFrame::MOVE_FORWARD);
+    }
+
+    #[test]
+    fn jumps_near_creep() {
+        let mobs = [mob(2.0, 0.0, Vector3::ZERO)];
+        let frame = Bot::default().decide(Vector3::ZERO, true, &mobs);
+        assert!(frame.contains(InputFrame::JUMP));
+        assert!(frame.contains(InputFrame::MOVE_RIGHT));
+    }
+
+    #[test]
+    fn evades_approaching_creep() {
+        // bot chases the nearest creep, other creep on the right runs to him
+        let mobs = [
+            mob(0.0, -2.0, Vector3::ZERO),
+            mob(2.5, 0.0, Vector3::new(-5.0, 0.0, 0.0)),
+        ];
+        let frame = Bot::default().decide(Vector3::ZERO, true, &mobs);
+        assert!(frame.contains(InputFrame::MOVE_LEFT));
+        assert!(!frame.contains(InputFrame::JUMP));
+    }
+
+    #[test]
+    fn percentile_of_sorted_values() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(percentile(&values, 0.5), 3.0);
+        assert_eq!(percentile(&values, 0.9), 5.0);
+        assert_eq!(percentile(&[], 0.5), 0.0);
+    }
+}
@@ -0,0 +1,22 @@
+//! This module store helpers for user arguments of command line, they are
+//! passed to the game after '--' (tools read options like '--output=path').
+
+use godot::{classes::Os, prelude::*};
+
+/// User arguments from command line.
+pub fn user_arguments() -> Vec<String> {
+    Os::singleton()
+        .get_cmdline_user_args()
+        .as_slice()
+        .iter()
+        .map(|argument| argument.to_string())
+        .collect()
+}
+
+/// Value of '--name=value' argument from command line.
+pub fn argument(arguments: &[String], name: &str) -> Option<String> {
+    let prefix = format!("--{name}=");
+    arguments
+        .iter()
+        .find_map(|argument| argument.strip_prefix(&prefix).map(String::from))
+}
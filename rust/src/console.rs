@@ -0,0 +1,380 @@
+//! This module store a developer console. Console is an overlay with a
+//! command line, it is opened and closed by 'toggle_console' action (the
+//! '`' key) and pauses the game while it is open.
+//!
+//! Console is attached by 'MainScene' and 'BaseLevel', commands which need
+//! the scene ('give', 'spawn') are sent to it with signals:
+//! - `help` - list of commands;
+//! - `god` - toggle god mode, the player can't die and returns to spawn after fall;
+//! - `give shield|slow` - activate improvement;
+//! - `spawn mob <type> [n]` - spawn creeps of archetype;
+//! - `level <id or path>` - change scene to level ('menu' for main scene);
+//! - `timescale <x>` - change speed of the game;
+//! - `seed <n>` - seed random generator;
+//! - `clear` - clear output.
+
+use crate::{
+    levels,
+    mob::archetype::find_archetype,
+    player::{PLAYER_GROUP, Player},
+};
+use godot::{
+    classes::{
+        CanvasLayer, Engine, FileAccess, ICanvasLayer, InputEvent, LineEdit, PanelContainer,
+        ResourceLoader, RichTextLabel, VBoxContainer,
+        control::{LayoutPreset, SizeFlags},
+        node::ProcessMode,
+    },
+    global::seed,
+    obj::WithBaseField,
+    prelude::*,
+};
+
+/// Action which opens and closes the console.
+const TOGGLE_ACTION: &str = "toggle_console";
+
+/// Layer of the console, above all UI.
+const CONSOLE_LAYER: i32 = 100;
+
+/// Height of the console, in px.
+const CONSOLE_HEIGHT: f32 = 220.0;
+
+/// Scene of the main menu and the arena.
+const MENU_SCENE: &str = "res://scenes/main.tscn";
+
+/// Items which can be given by 'give' command.
+const GIVE_ITEMS: [&str; 2] = ["shield", "slow"];
+
+/// Help text of the console.
+const HELP: &str = "commands: god, give shield|slow, spawn mob <type> [n], \
+    level <id or path>, timescale <x>, seed <n>, clear";
+
+/// This enum store all commands of the console.
+#[derive(Debug, PartialEq)]
+pub enum ConsoleCommand {
+    Help,
+    God,
+    Give(&'static str),
+    Spawn { archetype_id: String, count: i64 },
+    Level(String),
+    TimeScale(f64),
+    Seed(i64),
+    Clear,
+}
+
+/// Parse a number argument of command.
+fn parse_number<T: std::str::FromStr>(value: Option<&str>, usage: &str) -> Result<T, String> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("usage: {usage}"))
+}
+
+/// Parse command line of the console.
+pub fn parse_command(line: &str) -> Result<ConsoleCommand, String> {
+    let mut words = line.split_whitespace();
+    let Some(name) = words.next() else {
+        return Err(HELP.to_string());
+    };
+
+    let command = match name {
+        "help" => ConsoleCommand::Help,
+        "god" => ConsoleCommand::God,
+        "clear" => ConsoleCommand::Clear,
+        "give" => {
+            let item = words.next().unwrap_or_default();
+            let item = GIVE_ITEMS
+                .into_iter()
+                .find(|known| *known == item)
+                .ok_or("usage: give shield|slow")?;
+            ConsoleCommand::Give(item)
+        }
+        "spawn" => {
+            if words.next() != Some("mob") {
+                return Err("usage: spawn mob <type> [n]".to_string());
+            }
+            let archetype_id = words.next().unwrap_or_default();
+            if find_archetype(archetype_id).is_none() {
+                return Err(format!("unknown mob type '{archetype_id}'"));
+            }
+            let count = match words.next() {
+                Some(count) => parse_number(Some(count), "spawn mob <type> [n]")?,
+                None => 1,
+            };
+            if count < 1 {
+                return Err("amount of mobs must be positive".to_string());
+            }
+            ConsoleCommand::Spawn {
+                archetype_id: archetype_id.to_string(),
+                count,
+            }
+        }
+        "level" => {
+            let id = words.next().ok_or("usage: level <id or path>")?;
+            ConsoleCommand::Level(id.to_string())
+        }
+        "timescale" => {
+            let scale: f64 = parse_number(words.next(), "timescale <x>")?;
+            if !(scale.is_finite() && scale > 0.0) {
+                return Err("time scale must be a positive number".to_string());
+            }
+            ConsoleCommand::TimeScale(scale)
+        }
+        "seed" => ConsoleCommand::Seed(parse_number(words.next(), "seed <n>")?),
+        _ => return Err(format!("unknown command '{name}', type 'help'")),
+    };
+
+    Ok(command)
+}
+
+/// Find path to level by id, path is returned as is.
+fn level_path(id: &str) -> Option<String> {
+    if id == "menu" || id == "arena" {
+        return Some(MENU_SCENE.to_string());
+    }
+    if id.contains("://") {
+        return Some(id.to_string());
+    }
+
+    let scene = format!("res://scenes/levels/{id}.tscn");
+    if ResourceLoader::singleton().exists(&scene) {
+        return Some(scene);
+    }
+
+    [
+        format!("res://levels/{id}.json"),
+        format!("user://levels/{id}.json"),
+    ]
+    .into_iter()
+    .find(|path| FileAccess::file_exists(path))
+}
+
+/// This class is a developer console.
+#[derive(GodotClass)]
+#[class(base = CanvasLayer)]
+pub struct DevConsole {
+    /// Output of commands.
+    output: Option<Gd<RichTextLabel>>,
+
+    /// Command line.
+    input: Option<Gd<LineEdit>>,
+
+    /// Indicates whether the game was paused by the console.
+    paused_game: bool,
+
+    base: Base<CanvasLayer>,
+}
+
+#[godot_api]
+impl ICanvasLayer for DevConsole {
+    fn init(base: Base<CanvasLayer>) -> Self {
+        Self {
+            output: None,
+            input: None,
+            paused_game: false,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        // console works while the game is paused
+        self.base_mut().set_layer(CONSOLE_LAYER);
+        self.base_mut().set_process_mode(ProcessMode::ALWAYS);
+
+        // create panel with output and command line
+        let mut panel = PanelContainer::new_alloc();
+        panel.set_anchors_preset(LayoutPreset::TOP_WIDE);
+        panel.set_custom_minimum_size(Vector2::new(0.0, CONSOLE_HEIGHT));
+
+        let mut output = RichTextLabel::new_alloc();
+        output.set_scroll_follow(true);
+        output.set_selection_enabled(true);
+        output.set_v_size_flags(SizeFlags::EXPAND_FILL);
+
+        let mut input = LineEdit::new_alloc();
+        input.set_placeholder("type 'help' for commands");
+        input
+            .signals()
+            .text_submitted()
+            .connect_obj(&self.to_gd(), Self::on_text_submitted);
+
+        let mut container = VBoxContainer::new_alloc();
+        container.add_child(&output);
+        container.add_child(&input);
+        panel.add_child(&container);
+        self.base_mut().add_child(&panel);
+
+        self.output = Some(output);
+        self.input = Some(input);
+        self.base_mut().hide();
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        if !event.is_action_pressed(TOGGLE_ACTION) {
+            return;
+        }
+
+        // '`' must not be typed to the command line
+        if let Some(mut viewport) = self.base().get_viewport() {
+            viewport.set_input_as_handled();
+        }
+
+        let visible = self.base().is_visible();
+        self.set_open(!visible);
+    }
+}
+
+#[godot_api]
+impl DevConsole {
+    /// Emitted by 'give' command, item is 'shield' or 'slow'.
+    #[signal]
+    pub fn give_requested(item: GString);
+
+    /// Emitted by 'spawn mob' command.
+    #[signal]
+    pub fn spawn_requested(archetype_id: GString, count: i64);
+
+    /// Print line to the console.
+    pub fn print(&mut self, text: &str) {
+        if let Some(output) = self.output.as_mut() {
+            output.add_text(&format!("{text}\n"));
+        }
+    }
+
+    /// Open or close the console, the game is paused while it is open.
+    fn set_open(&mut self, open: bool) {
+        let mut tree = self.base().get_tree().unwrap();
+        if open {
+            self.paused_game = !tree.is_paused();
+            tree.set_pause(true);
+            self.base_mut().show();
+            if let Some(input) = self.input.as_mut() {
+                input.grab_focus();
+            }
+        } else {
+            if self.paused_game {
+                tree.set_pause(false);
+            }
+            self.paused_game = false;
+            self.base_mut().hide();
+        }
+    }
+
+    /// Run command from command line.
+    fn on_text_submitted(&mut self, text: GString) {
+        if let Some(input) = self.input.as_mut() {
+            input.clear();
+        }
+
+        let line = text.to_string();
+        self.print(&format!("> {line}"));
+        match parse_command(&line) {
+            Ok(command) => self.run(command),
+            Err(message) => self.print(&message),
+        }
+    }
+
+    /// Run parsed command.
+    fn run(&mut self, command: ConsoleCommand) {
+        match command {
+            ConsoleCommand::Help => self.print(HELP),
+            ConsoleCommand::Clear => {
+                if let Some(output) = self.output.as_mut() {
+                    output.clear();
+                }
+            }
+            ConsoleCommand::God => {
+                let Some(mut player) = self.player() else {
+                    self.print("player not found");
+                    return;
+                };
+                let god_mode = !player.bind().god_mode;
+                player.bind_mut().god_mode = god_mode;
+                self.print(if god_mode {
+                    "god mode on"
+                } else {
+                    "god mode off"
+                });
+            }
+            ConsoleCommand::Give(item) => {
+                self.signals().give_requested().emit(&GString::from(item));
+            }
+            ConsoleCommand::Spawn {
+                archetype_id,
+                count,
+            } => {
+                self.signals()
+                    .spawn_requested()
+                    .emit(&GString::from(&archetype_id), count);
+            }
+            ConsoleCommand::Level(id) => {
+                let Some(path) = level_path(&id) else {
+                    self.print(&format!("level '{id}' not found"));
+                    return;
+                };
+                self.set_open(false);
+                levels::change_scene(&mut self.base().get_tree().unwrap(), &path);
+            }
+            ConsoleCommand::TimeScale(scale) => {
+                Engine::singleton().set_time_scale(scale);
+                self.print(&format!("time scale {scale}"));
+            }
+            ConsoleCommand::Seed(value) => {
+                seed(value);
+                self.print(&format!("random seed {value}"));
+            }
+        }
+    }
+
+    /// Player of the scene.
+    fn player(&self) -> Option<Gd<Player>> {
+        self.base()
+            .get_tree()?
+            .get_first_node_in_group(PLAYER_GROUP)?
+            .try_cast::<Player>()
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_simple_commands() {
+        assert_eq!(parse_command("god"), Ok(ConsoleCommand::God));
+        assert_eq!(
+            parse_command("give shield"),
+            Ok(ConsoleCommand::Give("shield"))
+        );
+        assert_eq!(
+            parse_command("timescale 0.5"),
+            Ok(ConsoleCommand::TimeScale(0.5))
+        );
+        assert_eq!(parse_command("seed 42"), Ok(ConsoleCommand::Seed(42)));
+    }
+
+    #[test]
+    fn parse_spawn() {
+        assert_eq!(
+            parse_command("spawn mob chaser 3"),
+            Ok(ConsoleCommand::Spawn {
+                archetype_id: "chaser".to_string(),
+                count: 3
+            })
+        );
+        assert!(parse_command("spawn mob unknown").is_err());
+        assert!(parse_command("spawn mob chaser 0").is_err());
+        assert!(parse_command("spawn mob chaser -5").is_err());
+        assert!(parse_command("spawn chaser").is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse_command("").is_err());
+        assert!(parse_command("fly").is_err());
+        assert!(parse_command("give sword").is_err());
+        assert!(parse_command("timescale -1").is_err());
+        assert!(parse_command("timescale nan").is_err());
+        assert!(parse_command("timescale inf").is_err());
+    }
+}
@@ -0,0 +1,490 @@
+//! This module store a debug overlay for tuning of mobs and collisions. Overlay
+//! is opened and closed by 'toggle_debug_draw' action (the 'F3' key) and draws
+//! over the scene (without depth test):
+//! - curves of all 'Path3D' (blue) with progress of 'PathFollow3D' (white cross);
+//! - spawn points of 'SpawnPath' and 'Spawners' of levels (yellow);
+//! - velocity of the player and creeps (cyan), creeps on a path have no velocity;
+//! - normals of player collisions with creeps, green if it is a stomp
+//!   (same test as in 'Player::physics_process'), otherwise red;
+//! - collision shapes (box, sphere, capsule and cylinder, gray);
+//! - panel with FPS, amount of live creeps and active power-ups.
+//!
+//! Overlay is attached by 'MainScene' and 'BaseLevel' in builds with 'dev-tools'.
+
+use crate::{
+    main_scene::MainScene,
+    mob::{MOB_GROUP, Mob},
+    player::{PLAYER_GROUP, Player, is_stomp},
+};
+use godot::{
+    classes::{
+        BoxShape3D, CanvasLayer, CapsuleShape3D, CollisionShape3D, CylinderShape3D, Engine,
+        INode3D, ImmediateMesh, InputEvent, Label, Marker3D, MeshInstance3D, Path3D, PathFollow3D,
+        SphereShape3D, StandardMaterial3D,
+        base_material_3d::{Flags, ShadingMode},
+        mesh::PrimitiveType,
+    },
+    obj::WithBaseField,
+    prelude::*,
+};
+
+/// Action which shows and hides the overlay.
+const TOGGLE_ACTION: &str = "toggle_debug_draw";
+
+/// Layer of the panel, below the developer console.
+const PANEL_LAYER: i32 = 90;
+
+/// Length of velocity vector for 1 m/s, in m.
+const VELOCITY_SCALE: f32 = 0.25;
+
+/// Length of collision normal, in m.
+const NORMAL_LENGTH: f32 = 1.5;
+
+/// Size of markers, in m.
+const MARKER_SIZE: f32 = 0.5;
+
+/// Segments of circles of collision shapes.
+const CIRCLE_SEGMENTS: usize = 16;
+
+/// Color of paths.
+const PATH_COLOR: Color = Color::from_rgb(0.3, 0.5, 1.0);
+
+/// Color of progress on paths.
+const PROGRESS_COLOR: Color = Color::from_rgb(1.0, 1.0, 1.0);
+
+/// Color of spawn points.
+const SPAWN_COLOR: Color = Color::from_rgb(1.0, 0.9, 0.2);
+
+/// Color of velocity vectors.
+const VELOCITY_COLOR: Color = Color::from_rgb(0.2, 1.0, 1.0);
+
+/// Color of collision normal which squashes the creep.
+const STOMP_COLOR: Color = Color::from_rgb(0.2, 1.0, 0.3);
+
+/// Color of collision normal which kills the player.
+const HIT_COLOR: Color = Color::from_rgb(1.0, 0.25, 0.2);
+
+/// Color of collision shapes.
+const SHAPE_COLOR: Color = Color::from_rgba(0.8, 0.8, 0.8, 0.6);
+
+/// Edges of a box with half size, centered at origin.
+pub fn box_edges(half: Vector3) -> Vec<(Vector3, Vector3)> {
+    let corner = |x: f32, y: f32, z: f32| Vector3::new(half.x * x, half.y * y, half.z * z);
+
+    let mut edges = Vec::with_capacity(12);
+    for a in [-1.0, 1.0] {
+        for b in [-1.0, 1.0] {
+            edges.push((corner(-1.0, a, b), corner(1.0, a, b)));
+            edges.push((corner(a, -1.0, b), corner(a, 1.0, b)));
+            edges.push((corner(a, b, -1.0), corner(a, b, 1.0)));
+        }
+    }
+    edges
+}
+
+/// Points of a closed circle in plane of axes 'u' and 'v', first point is repeated at the end.
+pub fn circle(center: Vector3, radius: f32, u: Vector3, v: Vector3) -> Vec<Vector3> {
+    (0..=CIRCLE_SEGMENTS)
+        .map(|index| {
+            let angle = std::f32::consts::TAU * index as f32 / CIRCLE_SEGMENTS as f32;
+            center + (u * angle.cos() + v * angle.sin()) * radius
+        })
+        .collect()
+}
+
+/// This struct store lines of one frame.
+#[derive(Default)]
+struct DebugLines {
+    /// Start, end and color of every line.
+    lines: Vec<(Vector3, Vector3, Color)>,
+}
+
+impl DebugLines {
+    /// Line from point to point.
+    fn line(&mut self, from: Vector3, to: Vector3, color: Color) {
+        self.lines.push((from, to, color));
+    }
+
+    /// Lines between neighbour points.
+    fn polyline(&mut self, points: &[Vector3], color: Color) {
+        for pair in points.windows(2) {
+            self.line(pair[0], pair[1], color);
+        }
+    }
+
+    /// Cross of three lines along axes.
+    fn cross(&mut self, at: Vector3, color: Color) {
+        for axis in [Vector3::RIGHT, Vector3::UP, Vector3::BACK] {
+            let offset = axis * MARKER_SIZE * 0.5;
+            self.line(at - offset, at + offset, color);
+        }
+    }
+
+    /// Wireframe of collision shape, unsupported shapes are skipped.
+    fn shape(&mut self, shape: &Gd<CollisionShape3D>) {
+        let Some(resource) = shape.get_shape() else {
+            return;
+        };
+        let transform = shape.get_global_transform();
+
+        let mut local = Self::default();
+        if let Ok(shape) = resource.clone().try_cast::<BoxShape3D>() {
+            for (from, to) in box_edges(shape.get_size() / 2.0) {
+                local.line(from, to, SHAPE_COLOR);
+            }
+        } else if let Ok(shape) = resource.clone().try_cast::<SphereShape3D>() {
+            let radius = shape.get_radius();
+            let axes = [Vector3::RIGHT, Vector3::UP, Vector3::BACK];
+            for (u, v) in [(0, 1), (1, 2), (0, 2)] {
+                local.polyline(
+                    &circle(Vector3::ZERO, radius, axes[u], axes[v]),
+                    SHAPE_COLOR,
+                );
+            }
+        } else if let Ok(shape) = resource.clone().try_cast::<CapsuleShape3D>() {
+            // height of capsule includes hemispheres
+            let radius = shape.get_radius();
+            local.upright(
+                radius,
+                shape.get_height() / 2.0 - radius,
+                shape.get_height() / 2.0,
+            );
+        } else if let Ok(shape) = resource.try_cast::<CylinderShape3D>() {
+            let half_height = shape.get_height() / 2.0;
+            local.upright(shape.get_radius(), half_height, half_height);
+        }
+
+        for (from, to, color) in local.lines {
+            self.line(transform * from, transform * to, color);
+        }
+    }
+
+    /// Wireframe of vertical capsule or cylinder: rings at 'ring_height'
+    /// above and below the center and sides up to 'half_height'.
+    fn upright(&mut self, radius: f32, ring_height: f32, half_height: f32) {
+        for y in [-ring_height, ring_height] {
+            let ring = circle(Vector3::UP * y, radius, Vector3::RIGHT, Vector3::BACK);
+            self.polyline(&ring, SHAPE_COLOR);
+        }
+        for side in [
+            Vector3::RIGHT,
+            Vector3::LEFT,
+            Vector3::BACK,
+            Vector3::FORWARD,
+        ] {
+            let side = side * radius;
+            self.line(
+                side + Vector3::DOWN * half_height,
+                side + Vector3::UP * half_height,
+                SHAPE_COLOR,
+            );
+        }
+    }
+
+    /// Replace surfaces of mesh by lines.
+    fn commit(&self, mesh: &mut Gd<ImmediateMesh>) {
+        mesh.clear_surfaces();
+
+        // surface can't be empty
+        if self.lines.is_empty() {
+            return;
+        }
+
+        mesh.surface_begin(PrimitiveType::LINES);
+        for &(from, to, color) in &self.lines {
+            mesh.surface_set_color(color);
+            mesh.surface_add_vertex(from);
+            mesh.surface_add_vertex(to);
+        }
+        mesh.surface_end();
+    }
+}
+
+/// This class is a debug overlay of the scene, it draws data of the parent node.
+#[derive(GodotClass)]
+#[class(base = Node3D)]
+pub struct DebugOverlay {
+    /// Mesh with lines of the last frame.
+    mesh: Option<Gd<ImmediateMesh>>,
+
+    /// Layer with text panel.
+    panel: Option<Gd<CanvasLayer>>,
+
+    /// Text of the panel.
+    label: Option<Gd<Label>>,
+
+    base: Base<Node3D>,
+}
+
+#[godot_api]
+impl INode3D for DebugOverlay {
+    fn init(base: Base<Node3D>) -> Self {
+        Self {
+            mesh: None,
+            panel: None,
+            label: None,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        // lines are in global coordinates
+        self.base_mut().set_as_top_level(true);
+
+        // lines are drawn by vertex color over the scene
+        let mut material = StandardMaterial3D::new_gd();
+        material.set_shading_mode(ShadingMode::UNSHADED);
+        material.set_flag(Flags::ALBEDO_FROM_VERTEX_COLOR, true);
+        material.set_flag(Flags::DISABLE_DEPTH_TEST, true);
+
+        let mesh = ImmediateMesh::new_gd();
+        let mut mesh_instance = MeshInstance3D::new_alloc();
+        mesh_instance.set_mesh(&mesh);
+        mesh_instance.set_material_override(&material);
+        self.base_mut().add_child(&mesh_instance);
+
+        // create text panel
+        let mut panel = CanvasLayer::new_alloc();
+        panel.set_layer(PANEL_LAYER);
+        let mut label = Label::new_alloc();
+        label.set_position(Vector2::new(8.0, 8.0));
+        panel.add_child(&label);
+        self.base_mut().add_child(&panel);
+
+        self.mesh = Some(mesh);
+        self.panel = Some(panel);
+        self.label = Some(label);
+        self.set_open(false);
+    }
+
+    fn process(&mut self, _delta: f64) {
+        if !self.base().is_visible() {
+            return;
+        }
+        let Some(root) = self.base().get_parent() else {
+            return;
+        };
+
+        let mut lines = DebugLines::default();
+        draw_paths(&mut lines, &root);
+        draw_spawn_points(&mut lines, &root);
+        for shape in find_all::<CollisionShape3D>(&root, "CollisionShape3D") {
+            if !shape.is_disabled() && shape.is_visible_in_tree() {
+                lines.shape(&shape);
+            }
+        }
+
+        let player = root
+            .get_tree()
+            .and_then(|tree| tree.get_first_node_in_group(PLAYER_GROUP))
+            .and_then(|player| player.try_cast::<Player>().ok());
+        if let Some(player) = player.clone() {
+            draw_player(&mut lines, player);
+        }
+
+        // alive creeps
+        let mobs: Vec<Gd<Mob>> = root
+            .get_tree()
+            .map(|tree| tree.get_nodes_in_group(MOB_GROUP))
+            .unwrap_or_default()
+            .iter_shared()
+            .filter_map(|mob| mob.try_cast::<Mob>().ok())
+            .filter(|mob| mob.is_visible() && mob.bind().is_alive())
+            .collect();
+        for mob in &mobs {
+            let position = mob.get_global_position();
+            lines.line(
+                position,
+                position + mob.get_velocity() * VELOCITY_SCALE,
+                VELOCITY_COLOR,
+            );
+        }
+
+        if let Some(mesh) = self.mesh.as_mut() {
+            lines.commit(mesh);
+        }
+
+        // text panel
+        let text = panel_text(&root, player.as_ref(), mobs.len());
+        if let Some(label) = self.label.as_mut() {
+            label.set_text(&text);
+        }
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        if !event.is_action_pressed(TOGGLE_ACTION) {
+            return;
+        }
+
+        let visible = self.base().is_visible();
+        self.set_open(!visible);
+    }
+}
+
+#[godot_api]
+impl DebugOverlay {
+    /// Show or hide the overlay and the panel.
+    fn set_open(&mut self, open: bool) {
+        self.base_mut().set_visible(open);
+        if let Some(panel) = self.panel.as_mut() {
+            panel.set_visible(open);
+        }
+        if !open {
+            if let Some(mesh) = self.mesh.as_mut() {
+                mesh.clear_surfaces();
+            }
+        }
+    }
+}
+
+/// All descendants of root with class, including created in code.
+fn find_all<T: Inherits<Node>>(root: &Gd<Node>, class: &str) -> Vec<Gd<T>> {
+    root.find_children_ex("*")
+        .type_(class)
+        .owned(false)
+        .done()
+        .iter_shared()
+        .filter_map(|node| node.try_cast::<T>().ok())
+        .collect()
+}
+
+/// Curves of all paths and progress of path followers on them.
+fn draw_paths(lines: &mut DebugLines, root: &Gd<Node>) {
+    for path in find_all::<Path3D>(root, "Path3D") {
+        let Some(curve) = path.get_curve() else {
+            continue;
+        };
+
+        let transform = path.get_global_transform();
+        let points: Vec<Vector3> = curve
+            .get_baked_points()
+            .as_slice()
+            .iter()
+            .map(|&point| transform * point)
+            .collect();
+        lines.polyline(&points, PATH_COLOR);
+    }
+
+    for follow_path in find_all::<PathFollow3D>(root, "PathFollow3D") {
+        lines.cross(follow_path.get_global_position(), PROGRESS_COLOR);
+    }
+}
+
+/// Points of the spawn path of the arena and spawners of levels.
+fn draw_spawn_points(lines: &mut DebugLines, root: &Gd<Node>) {
+    let mut points = Vec::new();
+
+    if let Some(path) = root.try_get_node_as::<Path3D>("SpawnPath") {
+        if let Some(curve) = path.get_curve() {
+            let transform = path.get_global_transform();
+            points.extend(
+                (0..curve.get_point_count())
+                    .map(|index| transform * curve.get_point_position(index)),
+            );
+        }
+    }
+
+    if let Some(spawners) = root.try_get_node_as::<Node>("Spawners") {
+        points.extend(
+            find_all::<Marker3D>(&spawners, "Marker3D")
+                .iter()
+                .map(|marker| marker.get_global_position()),
+        );
+    }
+
+    for point in points {
+        lines.line(point, point + Vector3::UP * MARKER_SIZE * 4.0, SPAWN_COLOR);
+        lines.cross(point, SPAWN_COLOR);
+    }
+}
+
+/// Velocity of the player and normals of his collisions with creeps.
+fn draw_player(lines: &mut DebugLines, mut player: Gd<Player>) {
+    let position = player.get_global_position();
+    lines.line(
+        position,
+        position + player.get_velocity() * VELOCITY_SCALE,
+        VELOCITY_COLOR,
+    );
+
+    // collisions of the last move are checked in the next physics tick
+    for index in 0..player.get_slide_collision_count() {
+        let Some(collision) = player.get_slide_collision(index) else {
+            continue;
+        };
+        let is_mob = collision
+            .get_collider()
+            .and_then(|collider| collider.try_cast::<Node>().ok())
+            .is_some_and(|collider| collider.is_in_group(MOB_GROUP));
+        if !is_mob {
+            continue;
+        }
+
+        let normal = collision.get_normal();
+        let color = if is_stomp(normal) {
+            STOMP_COLOR
+        } else {
+            HIT_COLOR
+        };
+        let point = collision.get_position();
+        lines.line(point, point + normal * NORMAL_LENGTH, color);
+    }
+}
+
+/// Text of the panel.
+fn panel_text(root: &Gd<Node>, player: Option<&Gd<Player>>, mob_count: usize) -> String {
+    let mut power_ups = Vec::new();
+    if player.is_some_and(|player| player.bind().shield_active) {
+        power_ups.push("shield");
+    }
+    let slow_creeps = root
+        .clone()
+        .try_cast::<MainScene>()
+        .is_ok_and(|main_scene| main_scene.bind().is_slow_creeps());
+    if slow_creeps {
+        power_ups.push("slow creeps");
+    }
+
+    format!(
+        "FPS: {}\nCreeps: {mob_count}\nPower-ups: {}",
+        Engine::singleton().get_frames_per_second(),
+        if power_ups.is_empty() {
+            "none".to_string()
+        } else {
+            power_ups.join(", ")
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn box_has_twelve_edges_along_axes() {
+        let half = Vector3::new(1.0, 2.0, 3.0);
+        let edges = box_edges(half);
+        assert_eq!(edges.len(), 12);
+
+        // every edge is parallel to an axis and as long as the box
+        for (from, to) in edges {
+            let length = (to - from).abs();
+            assert!(
+                length == Vector3::new(2.0, 0.0, 0.0)
+                    || length == Vector3::new(0.0, 4.0, 0.0)
+                    || length == Vector3::new(0.0, 0.0, 6.0)
+            );
+        }
+    }
+
+    #[test]
+    fn circle_is_closed_and_has_radius() {
+        let center = Vector3::new(1.0, 1.0, 1.0);
+        let points = circle(center, 2.0, Vector3::RIGHT, Vector3::BACK);
+        assert_eq!(points.len(), CIRCLE_SEGMENTS + 1);
+        assert!(points[0].distance_to(points[CIRCLE_SEGMENTS]) < 1e-5);
+        for point in points {
+            assert!((point.distance_to(center) - 2.0).abs() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! This module store an adaptive difficulty controller. Controller watches
+//! how the player plays (deaths, time between squashes, near misses) and
+//! moves difficulty level up or down. Level changes spawn rate, speed of
+//! creeps and chance of power-ups inside bounds set in the editor.
+
+use crate::{
+    mob::{MOB_GROUP, Mob},
+    player::{PLAYER_GROUP, Player},
+};
+use godot::{classes::INode, prelude::*};
+
+/// Difficulty level at the start, between easiest (0) and hardest (1).
+const DEFAULT_LEVEL: f64 = 0.5;
+
+/// This class change difficulty by performance of the player.
+#[derive(GodotClass)]
+#[class(base = Node)]
+pub struct DifficultyController {
+    /// Adjust difficulty, if disabled all factors are 1.
+    #[export]
+    enabled: bool,
+
+    /// How often difficulty is adjusted, in secs.
+    #[export]
+    evaluation_interval: f64,
+
+    /// Maximum change of level in one evaluation.
+    #[export]
+    adjust_step: f64,
+
+    /// How much level drops if the player dies.
+    #[export]
+    death_step: f64,
+
+    /// Expected time between squashes for current level, in secs.
+    #[export]
+    target_squash_interval: f64,
+
+    /// Distance to creep which counts as near miss, in m.
+    #[export]
+    near_miss_distance: f32,
+
+    /// Spawn rate factor on the easiest level.
+    #[export]
+    min_spawn_rate: f64,
+
+    /// Spawn rate factor on the hardest level.
+    #[export]
+    max_spawn_rate: f64,
+
+    /// Creep speed factor on the easiest level.
+    #[export]
+    min_creep_speed: f64,
+
+    /// Creep speed factor on the hardest level.
+    #[export]
+    max_creep_speed: f64,
+
+    /// Power-up chance factor on the hardest level.
+    #[export]
+    min_power_up_chance: f64,
+
+    /// Power-up chance factor on the easiest level.
+    #[export]
+    max_power_up_chance: f64,
+
+    /// Current level, 0 is the easiest and 1 is the hardest.
+    level: f64,
+
+    /// Time since last evaluation, in secs.
+    evaluation_time: f64,
+
+    /// Time since last squash, in secs.
+    time_since_squash: f64,
+
+    /// Squashes since last evaluation.
+    squashes: u32,
+
+    /// Near misses since last evaluation.
+    near_misses: u32,
+
+    /// Mobs which are near the player now, they are counted once.
+    near_mobs: Vec<InstanceId>,
+
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for DifficultyController {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            enabled: true,
+            evaluation_interval: 5.0,
+            adjust_step: 0.1,
+            death_step: 0.15,
+            target_squash_interval: 3.0,
+            near_miss_distance: 1.5,
+            min_spawn_rate: 0.7,
+            max_spawn_rate: 1.6,
+            min_creep_speed: 0.8,
+            max_creep_speed: 1.4,
+            min_power_up_chance: 0.5,
+            max_power_up_chance: 2.0,
+            level: DEFAULT_LEVEL,
+            evaluation_time: 0.0,
+            time_since_squash: 0.0,
+            squashes: 0,
+            near_misses: 0,
+            near_mobs: Vec::new(),
+            base
+        }
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        if !self.enabled {
+            return;
+        }
+
+        // performance is watched only while the player alive
+        let Some(player_position) = self.alive_player_position() else {
+            self.near_mobs.clear();
+            return;
+        };
+        self.update_near_misses(player_position);
+
+        // evaluate performance from time to time
+        self.time_since_squash += delta;
+        self.evaluation_time += delta;
+        if self.evaluation_time >= self.evaluation_interval {
+            self.evaluate();
+        }
+    }
+}
+
+#[godot_api]
+impl DifficultyController {
+    /// Player squashed a creep.
+    pub fn on_mob_squashed(&mut self) {
+        self.squashes += 1;
+        self.time_since_squash = 0.0;
+    }
+
+    /// Player died, make the game easier.
+    pub fn on_player_died(&mut self) {
+        if !self.enabled {
+            return;
+        }
+
+        self.set_level(self.level - self.death_step, "death");
+        self.reset_window();
+    }
+
+    /// Start watching new game, level stays from previous games.
+    pub fn on_new_game(&mut self) {
+        self.reset_window();
+        self.time_since_squash = 0.0;
+    }
+
+    /// Current level, 0 is the easiest and 1 is the hardest.
+    #[func]
+    pub fn get_level(&self) -> f64 {
+        self.level
+    }
+
+    /// Factor for spawn rate of creeps.
+    #[func]
+    pub fn get_spawn_rate_factor(&self) -> f64 {
+        self.factor(self.min_spawn_rate, self.max_spawn_rate)
+    }
+
+    /// Factor for speed of creeps.
+    #[func]
+    pub fn get_creep_speed_factor(&self) -> f64 {
+        self.factor(self.min_creep_speed, self.max_creep_speed)
+    }
+
+    /// Factor for chance of power-up, it is lower if the game is harder.
+    #[func]
+    pub fn get_power_up_factor(&self) -> f64 {
+        self.factor(self.max_power_up_chance, self.min_power_up_chance)
+    }
+
+    /// Factor between value for easiest and hardest level.
+    fn factor(&self, easiest: f64, hardest: f64) -> f64 {
+        if !self.enabled {
+            return 1.0;
+        }
+
+        // level 0.5 gives 1, if bounds allow it
+        let value = if self.level < DEFAULT_LEVEL {
+            easiest + (1.0 - easiest) * self.level / DEFAULT_LEVEL
+        } else {
+            1.0 + (hardest - 1.0) * (self.level - DEFAULT_LEVEL) / (1.0 - DEFAULT_LEVEL)
+        };
+        value.clamp(easiest.min(hardest), easiest.max(hardest))
+    }
+
+    /// Position of the player, 'None' if the player is dead or not found.
+    fn alive_player_position(&self) -> Option<Vector3> {
+        let player = self
+            .base()
+            .get_tree()?
+            .get_first_node_in_group(PLAYER_GROUP)?
+            .try_cast::<Player>()
+            .ok()?;
+
+        player
+            .bind()
+            .is_alive()
+            .then(|| player.get_global_position())
+    }
+
+    /// Count creeps which come near the player and leave him alive.
+    fn update_near_misses(&mut self, player_position: Vector3) {
+        let Some(tree) = self.base().get_tree() else {
+            return;
+        };
+
+        let mut near_mobs = Vec::new();
+        for mob in tree.get_nodes_in_group(MOB_GROUP).iter_shared() {
+            let Ok(mob) = mob.try_cast::<Mob>() else {
+                continue;
+            };
+
+            // squashed and pooled mobs are not near misses
+            if !mob.is_visible() || !mob.bind().is_alive() {
+                continue;
+            }
+
+            let id = mob.instance_id();
+            if mob.get_global_position().distance_to(player_position) < self.near_miss_distance {
+                near_mobs.push(id);
+            } else if self.near_mobs.contains(&id) {
+                // mob left the radius
+                self.near_misses += 1;
+            }
+        }
+        self.near_mobs = near_mobs;
+    }
+
+    /// Change level by performance since last evaluation.
+    fn evaluate(&mut self) {
+        // average time between squashes, time without squashes counts too
+        let squash_interval = if self.squashes > 0 {
+            self.evaluation_time / self.squashes as f64
+        } else {
+            self.evaluation_time.max(self.time_since_squash)
+        };
+
+        // fast squashes make the game harder, near misses make it easier
+        let squash_term = (self.target_squash_interval / squash_interval - 1.0).clamp(-1.0, 1.0);
+        let near_miss_term = (self.near_misses as f64 * 0.25).min(1.0);
+        let change = self.adjust_step * (squash_term - near_miss_term).clamp(-1.0, 1.0);
+
+        if change != 0.0 {
+            self.set_level(self.level + change, "performance");
+        }
+        self.reset_window();
+    }
+
+    /// Set new level in bounds and log it for tuning.
+    fn set_level(&mut self, level: f64, reason: &str) {
+        let level = level.clamp(0.0, 1.0);
+        if level == self.level {
+            return;
+        }
+        self.level = level;
+
+        godot_print!(
+            "difficulty level {:.2} ({reason}): spawn rate x{:.2}, creep speed x{:.2}, power-ups x{:.2}",
+            self.level,
+            self.get_spawn_rate_factor(),
+            self.get_creep_speed_factor(),
+            self.get_power_up_factor()
+        );
+    }
+
+    /// Start new evaluation window.
+    fn reset_window(&mut self) {
+        self.evaluation_time = 0.0;
+        self.squashes = 0;
+        self.near_misses = 0;
+        self.near_mobs.clear();
+    }
+}
@@ -0,0 +1,103 @@
+//! This module store the event bus. Bus is a global object (engine singleton
+//! 'EventBus', registered on load of the library) with typed signals of
+//! gameplay events, so systems don't need to know layout of the scene:
+//! scene publishes score, power-ups and state of the game, UI and the player
+//! subscribe to them.
+//!
+//! Events are emitted synchronously. 'mob_squashed' and 'player_hit' are
+//! emitted inside physics of the player and the mob, so subscribers must
+//! connect them with 'ConnectFlags::DEFERRED'.
+
+use crate::main_scene::Improvements;
+use godot::{
+    classes::{Engine, IObject},
+    prelude::*,
+};
+
+/// Name of the singleton.
+const EVENT_BUS: &str = "EventBus";
+
+/// This enum store all states of the game.
+#[derive(GodotConvert, Var, Export, Default, Clone, Copy, PartialEq, Debug)]
+#[godot(via = i64)]
+pub enum GameState {
+    /// Main menu is shown.
+    #[default]
+    Menu,
+
+    /// Run is in progress.
+    Playing,
+
+    /// The player died, summary is shown.
+    GameOver,
+
+    /// Level complete, summary is shown.
+    LevelComplete,
+}
+
+/// This class is a global bus of gameplay events.
+#[derive(GodotClass)]
+#[class(base = Object)]
+pub struct EventBus {
+    base: Base<Object>,
+}
+
+#[godot_api]
+impl IObject for EventBus {
+    fn init(base: Base<Object>) -> Self {
+        Self { base }
+    }
+}
+
+#[godot_api]
+impl EventBus {
+    /// Emitted if creep squashed (without armored stomps).
+    #[signal]
+    pub fn mob_squashed(score_value: i64);
+
+    /// Emitted if the player died.
+    #[signal]
+    pub fn player_hit();
+
+    /// Emitted if power-up activated or expired ('None').
+    #[signal]
+    pub fn power_up_changed(power_up: Improvements);
+
+    /// Emitted if score of the run changed.
+    #[signal]
+    pub fn score_changed(score: i64);
+
+    /// Emitted if state of the game changed.
+    #[signal]
+    pub fn game_state_changed(state: GameState);
+
+    /// The bus, it exists while the library is loaded.
+    pub fn singleton() -> Gd<Self> {
+        Engine::singleton()
+            .get_singleton(EVENT_BUS)
+            .expect("event bus is not registered")
+            .cast::<Self>()
+    }
+}
+
+/// Notify all systems about new state of the game.
+pub fn set_game_state(state: GameState) {
+    EventBus::singleton()
+        .signals()
+        .game_state_changed()
+        .emit(state);
+}
+
+/// Create the bus, must be called on load of the library.
+pub fn register() {
+    Engine::singleton().register_singleton(EVENT_BUS, &EventBus::new_alloc());
+}
+
+/// Remove the bus, must be called on unload of the library.
+pub fn unregister() {
+    let mut engine = Engine::singleton();
+    if let Some(bus) = engine.get_singleton(EVENT_BUS) {
+        engine.unregister_singleton(EVENT_BUS);
+        bus.free();
+    }
+}
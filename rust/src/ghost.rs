@@ -0,0 +1,337 @@
+//! This module store ghost runs. Ghost is the best run of the level, it is
+//! rendered as a translucent player which races alongside the live player.
+//!
+//! Ghost file store only positions of the player, so it is small and does
+//! not depend on the seed or the version of the game. Best run of every
+//! level is saved near the save file to 'user://ghosts/<level id>.ghost',
+//! ghost of other player can be copied there to race with him.
+//!
+//! Binary format (little endian):
+//! - magic 'SQGH' and version (u8);
+//! - length of level id (u16) and level id (UTF-8);
+//! - interval between samples in secs (f32);
+//! - amount of samples (u32), every sample is position x, y, z (f32).
+
+use crate::replay::{ReplayError, take};
+use godot::{
+    classes::{
+        DirAccess, FileAccess, INode3D, MeshInstance3D, StandardMaterial3D,
+        base_material_3d::{ShadingMode, Transparency},
+        file_access::ModeFlags,
+    },
+    global::Error,
+    obj::WithBaseField,
+    prelude::*,
+};
+
+/// First bytes of ghost file.
+const MAGIC: &[u8; 4] = b"SQGH";
+
+/// Version of ghost format.
+const VERSION: u8 = 1;
+
+/// Directory for ghosts, near the save file.
+const GHOST_DIRECTORY: &str = "user://ghosts";
+
+/// Amount of physics ticks between position samples.
+const SAMPLE_TICKS: u32 = 4;
+
+/// Color of the ghost.
+const GHOST_COLOR: Color = Color::from_rgba(0.6, 0.85, 1.0, 0.35);
+
+/// Path to ghost of level.
+fn ghost_path(level_id: &str) -> String {
+    format!("{GHOST_DIRECTORY}/{level_id}.ghost")
+}
+
+/// This struct store positions of the player in a run.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GhostRun {
+    /// Id of the level.
+    pub level_id: String,
+
+    /// Interval between samples, in secs.
+    pub sample_interval: f32,
+
+    /// Position of the player for every sample.
+    pub positions: Vec<Vector3>,
+}
+
+impl GhostRun {
+    /// Position of the ghost at time since start of the run, 'None' after the end.
+    pub fn position_at(&self, time: f64) -> Option<Vector3> {
+        let sample = time as f32 / self.sample_interval;
+        let index = sample.floor() as usize;
+
+        let from = *self.positions.get(index)?;
+        let to = self.positions.get(index + 1).copied().unwrap_or(from);
+        Some(from.lerp(to, sample.fract()))
+    }
+
+    /// Encode ghost to bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let level_id = self.level_id.as_bytes();
+        let level_id = &level_id[..level_id.len().min(u16::MAX as usize)];
+
+        let mut bytes = Vec::with_capacity(15 + level_id.len() + self.positions.len() * 12);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&(level_id.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(level_id);
+        bytes.extend_from_slice(&self.sample_interval.to_le_bytes());
+        bytes.extend_from_slice(&(self.positions.len() as u32).to_le_bytes());
+        for position in &self.positions {
+            for axis in [position.x, position.y, position.z] {
+                bytes.extend_from_slice(&axis.to_le_bytes());
+            }
+        }
+
+        bytes
+    }
+
+    /// Decode ghost from bytes.
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, ReplayError> {
+        let bytes = &mut bytes;
+
+        if take(bytes, MAGIC.len())? != MAGIC {
+            return Err(ReplayError::InvalidFormat("file is not a ghost"));
+        }
+        if take(bytes, 1)?[0] != VERSION {
+            return Err(ReplayError::InvalidFormat("unsupported version"));
+        }
+
+        let level_id_length = u16::from_le_bytes(take(bytes, 2)?.try_into().unwrap());
+        let level_id = String::from_utf8(take(bytes, level_id_length as usize)?.to_vec())
+            .map_err(|_| ReplayError::InvalidFormat("level id is not UTF-8"))?;
+
+        let sample_interval = f32::from_le_bytes(take(bytes, 4)?.try_into().unwrap());
+        if sample_interval <= 0.0 || !sample_interval.is_finite() {
+            return Err(ReplayError::InvalidFormat("invalid sample interval"));
+        }
+
+        let count = u32::from_le_bytes(take(bytes, 4)?.try_into().unwrap());
+        let mut positions = Vec::new();
+        for _ in 0..count {
+            let mut axes = [0.0; 3];
+            for axis in &mut axes {
+                *axis = f32::from_le_bytes(take(bytes, 4)?.try_into().unwrap());
+            }
+            positions.push(Vector3::new(axes[0], axes[1], axes[2]));
+        }
+
+        Ok(Self {
+            level_id,
+            sample_interval,
+            positions,
+        })
+    }
+
+    /// Load the best run of level.
+    pub fn load_best(level_id: &str) -> Result<Self, ReplayError> {
+        let path = ghost_path(level_id);
+        if !FileAccess::file_exists(&path) {
+            return Err(ReplayError::FileNotFound(path));
+        }
+
+        Self::from_bytes(FileAccess::get_file_as_bytes(&path).as_slice())
+    }
+
+    /// Save run as the best run of his level.
+    pub fn save_best(&self) -> Result<(), ReplayError> {
+        let error = DirAccess::make_dir_recursive_absolute(GHOST_DIRECTORY);
+        if error != Error::OK {
+            godot_warn!("failed to create '{GHOST_DIRECTORY}': {error:?}");
+        }
+
+        let path = ghost_path(&self.level_id);
+        let mut file =
+            FileAccess::open(&path, ModeFlags::WRITE).ok_or(ReplayError::Write(path.clone()))?;
+        file.store_buffer(&PackedByteArray::from(self.to_bytes().as_slice()));
+        file.close();
+
+        Ok(())
+    }
+}
+
+/// This struct record positions of the live player for a ghost.
+#[derive(Default)]
+pub struct GhostRecorder {
+    /// Recorded positions.
+    positions: Vec<Vector3>,
+
+    /// Interval between samples, in secs.
+    sample_interval: f32,
+
+    /// Physics ticks since start of recording.
+    ticks: u32,
+}
+
+impl GhostRecorder {
+    /// Record position of the player, must be called every physics tick.
+    pub fn record(&mut self, delta: f64, position: Vector3) {
+        if self.ticks % SAMPLE_TICKS == 0 {
+            self.sample_interval = delta as f32 * SAMPLE_TICKS as f32;
+            self.positions.push(position);
+        }
+        self.ticks += 1;
+    }
+
+    /// Finish recording and return the run.
+    pub fn finish(&mut self, level_id: &str) -> GhostRun {
+        self.ticks = 0;
+        GhostRun {
+            level_id: level_id.to_string(),
+            sample_interval: self.sample_interval,
+            positions: std::mem::take(&mut self.positions),
+        }
+    }
+
+    /// Remove recorded positions.
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.ticks = 0;
+    }
+}
+
+/// This class is a translucent player which repeats the best run.
+#[derive(GodotClass)]
+#[class(base = Node3D)]
+pub struct Ghost {
+    /// Run which the ghost repeats.
+    run: Option<GhostRun>,
+
+    /// Time since start of the run, in secs.
+    time: f64,
+
+    base: Base<Node3D>,
+}
+
+#[godot_api]
+impl INode3D for Ghost {
+    fn init(base: Base<Node3D>) -> Self {
+        Self {
+            run: None,
+            time: 0.0,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        // create model of the player
+        let mut pivot = Node3D::new_alloc();
+        pivot.set_name("Pivot");
+        let model = load::<PackedScene>("res://art/player.glb").instantiate_as::<Node3D>();
+        pivot.add_child(&model);
+        self.base_mut().add_child(&pivot);
+
+        // make model translucent
+        let mut material = StandardMaterial3D::new_gd();
+        material.set_albedo(GHOST_COLOR);
+        material.set_transparency(Transparency::ALPHA);
+        material.set_shading_mode(ShadingMode::UNSHADED);
+        for mesh in model
+            .find_children_ex("*")
+            .type_("MeshInstance3D")
+            .owned(false)
+            .done()
+            .iter_shared()
+        {
+            if let Ok(mut mesh) = mesh.try_cast::<MeshInstance3D>() {
+                mesh.set_material_override(&material);
+            }
+        }
+
+        self.base_mut().hide();
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        if self.run.is_none() || !self.base().is_visible() {
+            return;
+        }
+
+        // ghost disappears after the end of the run
+        self.time += delta;
+        let position = self.run.as_ref().and_then(|run| run.position_at(self.time));
+        let Some(position) = position else {
+            self.base_mut().hide();
+            return;
+        };
+
+        // look in direction of movement
+        let previous = self.base().get_position();
+        let direction = Vector3::new(position.x - previous.x, 0.0, position.z - previous.z);
+        if direction.length() > 0.001 {
+            self.base()
+                .get_node_as::<Node3D>("Pivot")
+                .look_at(previous + direction);
+        }
+
+        self.base_mut().set_position(position);
+    }
+}
+
+#[godot_api]
+impl Ghost {
+    /// Set run which the ghost repeats, ghost waits for 'start'.
+    pub fn set_run(&mut self, run: GhostRun) {
+        self.run = Some(run);
+        self.base_mut().hide();
+    }
+
+    /// Start the run from the beginning.
+    pub fn start(&mut self) {
+        let Some(position) = self.run.as_ref().and_then(|run| run.position_at(0.0)) else {
+            return;
+        };
+
+        self.time = 0.0;
+        self.base_mut().set_position(position);
+        self.base_mut().show();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run() -> GhostRun {
+        GhostRun {
+            level_id: "level_1".to_string(),
+            sample_interval: 0.5,
+            positions: vec![
+                Vector3::ZERO,
+                Vector3::new(2.0, 0.0, 0.0),
+                Vector3::new(2.0, 1.0, 4.0),
+            ],
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let original = run();
+        let decoded = GhostRun::from_bytes(&original.to_bytes()).unwrap();
+        assert_eq!(decoded, original);
+    }
+
+    #[test]
+    fn position_is_interpolated() {
+        let run = run();
+        assert_eq!(run.position_at(0.0), Some(Vector3::ZERO));
+        assert_eq!(run.position_at(0.25), Some(Vector3::new(1.0, 0.0, 0.0)));
+        assert_eq!(run.position_at(1.0), Some(Vector3::new(2.0, 1.0, 4.0)));
+        assert_eq!(run.position_at(1.5), None);
+    }
+
+    #[test]
+    fn recorder_samples_by_interval() {
+        let mut recorder = GhostRecorder::default();
+        for tick in 0..60 {
+            recorder.record(1.0 / 60.0, Vector3::new(tick as f32, 0.0, 0.0));
+        }
+
+        let run = recorder.finish("level_1");
+        assert_eq!(run.positions.len(), 15);
+        assert_eq!(run.positions[1], Vector3::new(4.0, 0.0, 0.0));
+        assert!((run.sample_interval - 1.0 / 15.0).abs() < 1e-6);
+    }
+}
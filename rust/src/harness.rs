@@ -0,0 +1,415 @@
+//! This module store a harness for automated gameplay tests. Harness boots
+//! a scene ('MainScene' or a level), drives the player with scripted input,
+//! advances physics ticks one by one and checks the outcome of the case.
+//!
+//! Harness does not need a GPU and returns non-zero exit code if any case
+//! fails, physics runs with fixed 60 ticks per second:
+//! ```sh
+//! godot --headless --fixed-fps 60 --path godot res://scenes/tools/run_tests.tscn
+//! godot --headless --fixed-fps 60 --path godot res://scenes/tools/run_tests.tscn -- shield_prevents_death
+//! ```
+
+use crate::{
+    command_line::user_arguments,
+    levels::BaseLevel,
+    main_scene::MainScene,
+    mob::{Mob, MobMovement, archetype::default_archetype},
+    mob_pool::MobPool,
+    player::{DeathCause, Player},
+    replay::{InputFrame, Replay, ReplayState},
+    spawn_director::SpawnDirector,
+};
+use godot::{
+    classes::{Engine, INode},
+    obj::WithBaseField,
+    prelude::*,
+};
+
+/// Physics ticks per second in tests.
+pub const TICKS_PER_SECOND: i32 = 60;
+
+/// Arena scene.
+pub const MAIN_SCENE: &str = "res://scenes/main.tscn";
+
+/// Hand-made level.
+const LEVEL_1_SCENE: &str = "res://scenes/levels/level_1.tscn";
+
+/// This enum store result of a case after a tick.
+pub enum Outcome {
+    /// Case needs more ticks.
+    Running,
+
+    /// Case passed.
+    Passed,
+
+    /// Case failed with reason.
+    Failed(String),
+}
+
+/// This struct describe one gameplay test.
+pub struct TestCase {
+    /// Name of the case, it can be passed in command line to run only this case.
+    pub name: &'static str,
+
+    /// Scene which is booted for the case.
+    pub scene: &'static str,
+
+    /// Case fails if it is still running after this amount of ticks.
+    pub max_ticks: u32,
+
+    /// Prepare booted scene, error fails the case.
+    pub setup: fn(&Gd<Node>) -> Result<(), String>,
+
+    /// Check the scene after every tick (tick starts from 1).
+    pub check: fn(&Gd<Node>, u32) -> Outcome,
+}
+
+/// All gameplay tests.
+static TEST_CASES: [TestCase; 6] = [
+    TestCase {
+        name: "stomp_increments_score",
+        scene: MAIN_SCENE,
+        max_ticks: 120,
+        setup: |scene| {
+            let mut player = start_arena(scene)?;
+            let position = player.get_position();
+            spawn_idle_mob(scene, position)?;
+
+            // the player falls on the creep
+            player.set_position(position + Vector3::UP * 3.0);
+            Ok(())
+        },
+        check: |scene, _| {
+            let score = scene.clone().cast::<MainScene>().bind().stats().score;
+            if score > 0 {
+                Outcome::Passed
+            } else if !player(scene).bind().is_alive() {
+                Outcome::Failed("player died instead of stomp".to_string())
+            } else {
+                Outcome::Running
+            }
+        },
+    },
+    TestCase {
+        name: "creep_kills_player",
+        scene: MAIN_SCENE,
+        max_ticks: 120,
+        setup: |scene| {
+            let mut player = start_arena(scene)?;
+            spawn_idle_mob(scene, player.get_position() + Vector3::RIGHT * 4.0)?;
+            play_input(&mut player, &[(120, InputFrame::MOVE_RIGHT)]);
+            Ok(())
+        },
+        check: |scene, _| expect_death(scene, DeathCause::Creep),
+    },
+    TestCase {
+        name: "shield_prevents_death",
+        scene: MAIN_SCENE,
+        max_ticks: 120,
+        setup: |scene| {
+            let mut player = start_arena(scene)?;
+            spawn_idle_mob(scene, player.get_position() + Vector3::RIGHT * 4.0)?;
+            player.bind_mut().shield_active = true;
+            play_input(&mut player, &[(90, InputFrame::MOVE_RIGHT)]);
+            Ok(())
+        },
+        check: |scene, tick| {
+            if !player(scene).bind().is_alive() {
+                Outcome::Failed(format!("player died with shield on tick {tick}"))
+            } else if tick >= 90 {
+                Outcome::Passed
+            } else {
+                Outcome::Running
+            }
+        },
+    },
+    TestCase {
+        name: "jump_leaves_floor",
+        scene: LEVEL_1_SCENE,
+        max_ticks: 90,
+        setup: |scene| {
+            // jump is held until the player lands after spawn
+            let mut player = player(scene);
+            play_input(
+                &mut player,
+                &[(30, InputFrame::default()), (60, InputFrame::JUMP)],
+            );
+            Ok(())
+        },
+        check: |scene, _| {
+            if player(scene).get_velocity().y > 1.0 {
+                Outcome::Passed
+            } else {
+                Outcome::Running
+            }
+        },
+    },
+    TestCase {
+        name: "fall_kills_player",
+        scene: LEVEL_1_SCENE,
+        max_ticks: 180,
+        setup: |scene| {
+            // far away from any floor of the level
+            player(scene).set_position(Vector3::new(1000.0, 0.0, 1000.0));
+            Ok(())
+        },
+        check: |scene, _| expect_death(scene, DeathCause::Fall),
+    },
+    TestCase {
+        name: "stomp_completes_level",
+        scene: LEVEL_1_SCENE,
+        max_ticks: 120,
+        setup: |scene| {
+            // all creeps except the last one are squashed at once
+            let mut mobs = scene
+                .get_node_as::<Node>("Mobs")
+                .get_children()
+                .iter_shared()
+                .filter_map(|node| node.try_cast::<Mob>().ok())
+                .collect::<Vec<_>>();
+            let mut last = mobs.pop().ok_or("level has no creeps")?;
+            for mut mob in mobs {
+                mob.bind_mut().squash();
+            }
+
+            // the player falls on the last creep, it stays on the place
+            last.bind_mut().initialize(MobMovement::Idle);
+            let position = last.get_global_position();
+            player(scene).set_global_position(position + Vector3::UP * 3.0);
+            Ok(())
+        },
+        check: |scene, _| {
+            let level = scene.clone().cast::<BaseLevel>();
+            if level.bind().is_complete() {
+                Outcome::Passed
+            } else if !player(scene).bind().is_alive() {
+                Outcome::Failed("player died instead of stomp".to_string())
+            } else {
+                Outcome::Running
+            }
+        },
+    },
+];
+
+/// Player of the scene.
+fn player(scene: &Gd<Node>) -> Gd<Player> {
+    scene.get_node_as::<Player>("Player")
+}
+
+/// Mark booted scene as automated, so cases don't replace records and replays of the player.
+fn mark_automated(scene: &Gd<Node>) {
+    if let Ok(mut main_scene) = scene.clone().try_cast::<MainScene>() {
+        main_scene.bind_mut().automated = true;
+    } else if let Ok(mut level) = scene.clone().try_cast::<BaseLevel>() {
+        level.bind_mut().automated = true;
+    }
+}
+
+/// Start new game in the arena without waves, return the player.
+fn start_arena(scene: &Gd<Node>) -> Result<Gd<Player>, String> {
+    let mut main_scene = scene
+        .clone()
+        .try_cast::<MainScene>()
+        .map_err(|_| "scene is not 'MainScene'".to_string())?;
+    main_scene.bind_mut().new_game();
+
+    // creeps are spawned only by the case
+    scene
+        .get_node_as::<SpawnDirector>("SpawnDirector")
+        .bind_mut()
+        .stop();
+    scene
+        .get_node_as::<MobPool>("MobPool")
+        .bind_mut()
+        .release_all();
+
+    Ok(player(scene))
+}
+
+/// Spawn a creep which stays on the place.
+fn spawn_idle_mob(scene: &Gd<Node>, position: Vector3) -> Result<(), String> {
+    let mut mob = scene
+        .get_node_as::<MobPool>("MobPool")
+        .bind_mut()
+        .acquire()
+        .ok_or("mob pool is full")?;
+
+    mob.set_position(position);
+    let mut mob = mob.bind_mut();
+    mob.set_archetype(default_archetype());
+    mob.initialize(MobMovement::Idle);
+    mob.alive();
+    Ok(())
+}
+
+/// Replace input of the player with runs of frames (amount of ticks and frame).
+fn play_input(player: &mut Gd<Player>, runs: &[(usize, InputFrame)]) {
+    let frames = runs
+        .iter()
+        .flat_map(|&(ticks, frame)| std::iter::repeat_n(frame, ticks))
+        .collect();
+
+    player.bind_mut().replay = ReplayState::Playing {
+        replay: Replay {
+            frames,
+            ..Replay::default()
+        },
+        tick: 0,
+    };
+}
+
+/// Pass if the player died by cause.
+fn expect_death(scene: &Gd<Node>, cause: DeathCause) -> Outcome {
+    let player = player(scene);
+    let player = player.bind();
+    if player.is_alive() {
+        Outcome::Running
+    } else if player.death_cause == cause {
+        Outcome::Passed
+    } else {
+        Outcome::Failed("player died by other cause".to_string())
+    }
+}
+
+/// This class run gameplay tests and quit with exit code 1 if any test fails.
+#[derive(GodotClass)]
+#[class(base = Node)]
+pub struct GameTestRunner {
+    /// Cases to run, index in 'TEST_CASES'.
+    cases: Vec<usize>,
+
+    /// Index in 'cases' of the running case.
+    current: usize,
+
+    /// Booted scene of the running case.
+    scene: Option<Gd<Node>>,
+
+    /// Ticks since start of the running case.
+    tick: u32,
+
+    /// Amount of failed cases.
+    failed: usize,
+
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for GameTestRunner {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            cases: Vec::new(),
+            current: 0,
+            scene: None,
+            tick: 0,
+            failed: 0,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        Engine::singleton().set_physics_ticks_per_second(TICKS_PER_SECOND);
+
+        // cases from command line, all cases if nothing passed
+        let names = user_arguments();
+
+        self.cases = (0..TEST_CASES.len())
+            .filter(|&index| {
+                names.is_empty() || names.contains(&TEST_CASES[index].name.to_string())
+            })
+            .collect();
+
+        for name in &names {
+            if !TEST_CASES.iter().any(|case| case.name == name) {
+                godot_print!("[FAIL] {name}: case not found");
+                self.failed += 1;
+            }
+        }
+
+        self.start_case();
+    }
+
+    fn physics_process(&mut self, _delta: f64) {
+        let Some(scene) = self.scene.clone() else {
+            return;
+        };
+        let case = &TEST_CASES[self.cases[self.current]];
+
+        self.tick += 1;
+        let outcome = match (case.check)(&scene, self.tick) {
+            Outcome::Running if self.tick >= case.max_ticks => {
+                Outcome::Failed(format!("timed out after {} ticks", case.max_ticks))
+            }
+            outcome => outcome,
+        };
+
+        match outcome {
+            Outcome::Running => return,
+            Outcome::Passed => godot_print!("[ OK ] {} ({} ticks)", case.name, self.tick),
+            Outcome::Failed(reason) => {
+                godot_print!("[FAIL] {}: {reason}", case.name);
+                self.failed += 1;
+            }
+        }
+
+        self.finish_case();
+        self.current += 1;
+        self.start_case();
+    }
+}
+
+#[godot_api]
+impl GameTestRunner {
+    /// Boot scene of the current case, or quit if all cases finished.
+    fn start_case(&mut self) {
+        let Some(&index) = self.cases.get(self.current) else {
+            self.quit();
+            return;
+        };
+        let case = &TEST_CASES[index];
+
+        let Some(scene) = try_load::<PackedScene>(case.scene)
+            .ok()
+            .and_then(|scene| scene.instantiate())
+        else {
+            godot_print!("[FAIL] {}: failed to load '{}'", case.name, case.scene);
+            self.failed += 1;
+            self.current += 1;
+            self.start_case();
+            return;
+        };
+
+        self.tick = 0;
+        mark_automated(&scene);
+        self.base_mut().add_child(&scene);
+        self.scene = Some(scene.clone());
+
+        if let Err(reason) = (case.setup)(&scene) {
+            godot_print!("[FAIL] {}: {reason}", case.name);
+            self.failed += 1;
+            self.finish_case();
+            self.current += 1;
+            self.start_case();
+        }
+    }
+
+    /// Remove scene of the finished case.
+    fn finish_case(&mut self) {
+        if let Some(mut scene) = self.scene.take() {
+            self.base_mut().remove_child(&scene);
+            scene.queue_free();
+        }
+    }
+
+    /// Print summary and quit with exit code 1 if any case failed.
+    fn quit(&mut self) {
+        godot_print!("{} cases run, {} failed", self.cases.len(), self.failed);
+
+        let exit_code = if self.failed == 0 { 0 } else { 1 };
+        self.base()
+            .get_tree()
+            .unwrap()
+            .quit_ex()
+            .exit_code(exit_code)
+            .done();
+    }
+}
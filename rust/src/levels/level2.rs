@@ -1,11 +1,10 @@
 use godot::{
     classes::{Path3D, PathFollow3D},
-    global::randf_range,
     prelude::*,
 };
 
 use super::{BaseLevel, get_text_mob_follow_path, get_text_mob_name, get_text_mob_path};
-use crate::mob::Mob;
+use crate::mob::{Mob, MobMovement};
 
 #[derive(GodotClass)]
 #[class(base = Node)]
@@ -58,11 +57,9 @@ impl INode for Level2 {
                 // get path
                 let path = self.base().try_get_node_as::<Path3D>(&get_text_mob_path(i));
 
-                // get speed
-                let follow_speed = randf_range(0.1, 0.34);
-
                 // init mob
-                mob.bind_mut().initialize(follow_path, path, follow_speed);
+                mob.bind_mut()
+                    .initialize(MobMovement::from_path(follow_path, path));
             }
         }
     }
@@ -70,7 +67,7 @@ impl INode for Level2 {
 
 #[godot_api]
 impl Level2 {
-    fn on_mob_squashed(&mut self) {
+    fn on_mob_squashed(&mut self, _score_value: i64) {
         let mobs = self
             .base()
             .get_tree()
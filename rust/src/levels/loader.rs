@@ -0,0 +1,762 @@
+//! This module store a logic for load levels from JSON files.
+//!
+//! Level file describe platforms, walls, spikes, mobs with their paths,
+//! player spawn and objectives. 'LevelLoader' build from this description
+//! a scene with the same hierarchy of names as hand-made levels have,
+//! so 'BaseLevel' works with both of them.
+//!
+//! Example of level file:
+//! ```json
+//! {
+//!     "id": "my_level",
+//!     "next_level": "res://levels/other_level.json",
+//!     "player_spawn": [0, 1, 0],
+//!     "platforms": [{ "position": [0, -1, 0], "size": [40, 2, 40] }],
+//!     "walls": [{ "position": [0, 3, -20], "size": [40, 7, 2], "rotation": 0 }],
+//!     "spikes": [[5, 0.5, 5]],
+//!     "mobs": [
+//!         { "path": [[-5, 0, -5], [5, 0, -5], [5, 0, 5]], "closed": true, "archetype": "jumper" },
+//!         { "path": [[0, 0, -10]], "navigation": true }
+//!     ],
+//!     "objectives": [{ "type": "squash_all" }]
+//! }
+//! ```
+//!
+//! Level can have a boss, such level is complete when the boss is defeated:
+//! ```json
+//! "boss": { "position": [0, 0, -10], "spawners": [[-15, 0, -15], [15, 0, -15]] }
+//! ```
+
+use super::{BaseLevel, get_text_mob_name, get_text_mob_path};
+use crate::{
+    mob::{Mob, WORLD_COLLISION_LAYER, archetype::find_archetype, boss::Boss},
+    mob_pool::MobPool,
+    player::Player,
+    ui::UserInterface,
+};
+use godot::{
+    classes::{
+        BoxMesh, BoxShape3D, Camera3D, CollisionShape3D, Curve3D, DirAccess, DirectionalLight3D,
+        FileAccess, IRefCounted, Json, Marker3D, Material, MeshInstance3D, NavigationMesh,
+        NavigationRegion3D, Path3D, PathFollow3D, RefCounted, SceneTree, StaticBody3D,
+        camera_3d::ProjectionType,
+        navigation_mesh::{ParsedGeometryType, SourceGeometryMode},
+    },
+    global::Error,
+    prelude::*,
+};
+use std::{f32::consts::FRAC_PI_4, fmt};
+
+/// Directories where levels files are searched.
+const LEVEL_DIRECTORIES: [&str; 2] = ["res://levels", "user://levels"];
+
+/// Extension of level files.
+const LEVEL_EXTENSION: &str = "json";
+
+/// Group of nodes which are used for bake navigation mesh.
+const NAVIGATION_GROUP: &str = "navigation";
+
+/// This enum store all errors which can be while loading a level.
+#[derive(Debug)]
+pub enum LevelLoadError {
+    /// Level file not found.
+    FileNotFound(String),
+
+    /// Level file is not valid JSON.
+    Parse { line: i32, message: String },
+
+    /// Field in level file is missing or has a wrong type.
+    InvalidField {
+        field: String,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for LevelLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FileNotFound(path) => write!(f, "level file '{path}' not found"),
+            Self::Parse { line, message } => write!(f, "line {line}: {message}"),
+            Self::InvalidField { field, expected } => {
+                write!(f, "field '{field}' must be {expected}")
+            }
+        }
+    }
+}
+
+/// This struct describe a box (platform or wall).
+pub struct BoxDescription {
+    /// Position of box center.
+    pub position: Vector3,
+
+    /// Size of the box.
+    pub size: Vector3,
+
+    /// Rotation around Y axis, in degrees.
+    pub rotation: f32,
+}
+
+/// This struct describe a mob and his path.
+pub struct MobDescription {
+    /// Points of the path, mob spawn in first point.
+    pub path: Vec<Vector3>,
+
+    /// Is the path closed (last point connected with first).
+    pub closed: bool,
+
+    /// Id of mob archetype ("normal" if not set).
+    pub archetype: Option<String>,
+
+    /// Mob chases the player by navigation mesh, path is used only for spawn.
+    pub navigation: bool,
+}
+
+/// This struct describe a boss and spawners of his minions.
+pub struct BossDescription {
+    /// Spawn position of the boss.
+    pub position: Vector3,
+
+    /// Points where minions of the boss appear.
+    pub spawners: Vec<Vector3>,
+}
+
+/// This enum store all objectives of level.
+pub enum Objective {
+    /// Squash all mobs on level.
+    SquashAll,
+
+    /// Squash at least given amount of mobs.
+    SquashCount(i64),
+}
+
+/// This struct store description of the level.
+pub struct LevelDescription {
+    pub id: String,
+    pub next_level: Option<String>,
+    pub player_spawn: Vector3,
+    pub platforms: Vec<BoxDescription>,
+    pub walls: Vec<BoxDescription>,
+    pub spikes: Vec<Vector3>,
+    pub mobs: Vec<MobDescription>,
+    pub boss: Option<BossDescription>,
+    pub objective: Objective,
+}
+
+// functions for parse values from JSON
+
+/// Get field from dictionary.
+fn get_field(dict: &Dictionary, field: &str) -> Option<Variant> {
+    dict.get(field).filter(|value| !value.is_nil())
+}
+
+/// Parse value as a dictionary.
+fn parse_dictionary(value: &Variant, field: &str) -> Result<Dictionary, LevelLoadError> {
+    value
+        .try_to::<Dictionary>()
+        .map_err(|_| LevelLoadError::InvalidField {
+            field: field.to_string(),
+            expected: "an object",
+        })
+}
+
+/// Parse value as an array.
+fn parse_array(value: &Variant, field: &str) -> Result<VariantArray, LevelLoadError> {
+    value
+        .try_to::<VariantArray>()
+        .map_err(|_| LevelLoadError::InvalidField {
+            field: field.to_string(),
+            expected: "an array",
+        })
+}
+
+/// Parse value as a number.
+fn parse_number(value: &Variant, field: &str) -> Result<f32, LevelLoadError> {
+    value
+        .try_to::<f64>()
+        .map(|number| number as f32)
+        .map_err(|_| LevelLoadError::InvalidField {
+            field: field.to_string(),
+            expected: "a number",
+        })
+}
+
+/// Parse value as a boolean.
+fn parse_bool(value: &Variant, field: &str) -> Result<bool, LevelLoadError> {
+    value
+        .try_to::<bool>()
+        .map_err(|_| LevelLoadError::InvalidField {
+            field: field.to_string(),
+            expected: "a boolean",
+        })
+}
+
+/// Parse value as a string.
+fn parse_string(value: &Variant, field: &str) -> Result<String, LevelLoadError> {
+    value
+        .try_to::<GString>()
+        .map(|string| string.to_string())
+        .map_err(|_| LevelLoadError::InvalidField {
+            field: field.to_string(),
+            expected: "a string",
+        })
+}
+
+/// Parse value as a point ('[x, y, z]').
+fn parse_vector3(value: &Variant, field: &str) -> Result<Vector3, LevelLoadError> {
+    let invalid = || LevelLoadError::InvalidField {
+        field: field.to_string(),
+        expected: "an array of 3 numbers",
+    };
+
+    let array = value.try_to::<VariantArray>().map_err(|_| invalid())?;
+    if array.len() != 3 {
+        return Err(invalid());
+    }
+
+    let mut coords = [0.0; 3];
+    for (i, coord) in coords.iter_mut().enumerate() {
+        *coord = parse_number(&array.at(i), field).map_err(|_| invalid())?;
+    }
+
+    Ok(Vector3::new(coords[0], coords[1], coords[2]))
+}
+
+/// Parse optional array of values with given parser.
+fn parse_list<T>(
+    dict: &Dictionary,
+    field: &str,
+    parser: impl Fn(&Variant, &str) -> Result<T, LevelLoadError>,
+) -> Result<Vec<T>, LevelLoadError> {
+    let Some(value) = get_field(dict, field) else {
+        return Ok(Vec::new());
+    };
+
+    parse_array(&value, field)?
+        .iter_shared()
+        .enumerate()
+        .map(|(i, item)| parser(&item, &format!("{field}[{i}]")))
+        .collect()
+}
+
+/// Parse a box description.
+fn parse_box(value: &Variant, field: &str) -> Result<BoxDescription, LevelLoadError> {
+    let dict = parse_dictionary(value, field)?;
+
+    let required = |name: &str| {
+        get_field(&dict, name).ok_or(LevelLoadError::InvalidField {
+            field: format!("{field}.{name}"),
+            expected: "set",
+        })
+    };
+
+    let rotation = match get_field(&dict, "rotation") {
+        Some(value) => parse_number(&value, &format!("{field}.rotation"))?,
+        None => 0.0,
+    };
+
+    Ok(BoxDescription {
+        position: parse_vector3(&required("position")?, &format!("{field}.position"))?,
+        size: parse_vector3(&required("size")?, &format!("{field}.size"))?,
+        rotation,
+    })
+}
+
+/// Parse a mob description.
+fn parse_mob(value: &Variant, field: &str) -> Result<MobDescription, LevelLoadError> {
+    let dict = parse_dictionary(value, field)?;
+
+    let path = parse_list(&dict, "path", parse_vector3).map_err(|error| match error {
+        LevelLoadError::InvalidField {
+            field: name,
+            expected,
+        } => LevelLoadError::InvalidField {
+            field: format!("{field}.{name}"),
+            expected,
+        },
+        error => error,
+    })?;
+
+    if path.is_empty() {
+        return Err(LevelLoadError::InvalidField {
+            field: format!("{field}.path"),
+            expected: "an array with at least one point",
+        });
+    }
+
+    let closed = match get_field(&dict, "closed") {
+        Some(value) => parse_bool(&value, &format!("{field}.closed"))?,
+        None => false,
+    };
+
+    let archetype = get_field(&dict, "archetype")
+        .map(|value| parse_string(&value, &format!("{field}.archetype")))
+        .transpose()?;
+
+    // splitters are not supported in levels, their children are not counted
+    if archetype
+        .as_deref()
+        .is_some_and(|id| find_archetype(id).is_none_or(|archetype| archetype.is_splitter()))
+    {
+        return Err(LevelLoadError::InvalidField {
+            field: format!("{field}.archetype"),
+            expected: "an id of mob archetype, except 'splitter'",
+        });
+    }
+
+    let navigation = match get_field(&dict, "navigation") {
+        Some(value) => parse_bool(&value, &format!("{field}.navigation"))?,
+        None => false,
+    };
+
+    Ok(MobDescription {
+        path,
+        closed,
+        archetype,
+        navigation,
+    })
+}
+
+/// Parse a boss description.
+fn parse_boss(value: &Variant, field: &str) -> Result<BossDescription, LevelLoadError> {
+    let dict = parse_dictionary(value, field)?;
+
+    let position = get_field(&dict, "position").ok_or(LevelLoadError::InvalidField {
+        field: format!("{field}.position"),
+        expected: "set",
+    })?;
+
+    let spawners = parse_list(&dict, "spawners", parse_vector3).map_err(|error| match error {
+        LevelLoadError::InvalidField {
+            field: name,
+            expected,
+        } => LevelLoadError::InvalidField {
+            field: format!("{field}.{name}"),
+            expected,
+        },
+        error => error,
+    })?;
+
+    Ok(BossDescription {
+        position: parse_vector3(&position, &format!("{field}.position"))?,
+        spawners,
+    })
+}
+
+/// Parse an objective.
+fn parse_objective(value: &Variant, field: &str) -> Result<Objective, LevelLoadError> {
+    let dict = parse_dictionary(value, field)?;
+
+    let kind = get_field(&dict, "type").ok_or(LevelLoadError::InvalidField {
+        field: format!("{field}.type"),
+        expected: "set",
+    })?;
+
+    match parse_string(&kind, &format!("{field}.type"))?.as_str() {
+        "squash_all" => Ok(Objective::SquashAll),
+        "squash_count" => {
+            let count = get_field(&dict, "count").ok_or(LevelLoadError::InvalidField {
+                field: format!("{field}.count"),
+                expected: "set",
+            })?;
+            let count = parse_number(&count, &format!("{field}.count"))?;
+            if count < 1.0 {
+                return Err(LevelLoadError::InvalidField {
+                    field: format!("{field}.count"),
+                    expected: "a number not less than 1",
+                });
+            }
+
+            Ok(Objective::SquashCount(count as i64))
+        }
+        _ => Err(LevelLoadError::InvalidField {
+            field: format!("{field}.type"),
+            expected: "'squash_all' or 'squash_count'",
+        }),
+    }
+}
+
+/// Check id of the level, it is a part of paths of saved replays and ghosts,
+/// so only letters, digits, '_' and '-' are allowed.
+fn is_valid_level_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '_' || char == '-')
+}
+
+impl LevelDescription {
+    /// Parse level description from JSON text.
+    pub fn from_json(text: &str) -> Result<Self, LevelLoadError> {
+        // parse JSON
+        let mut json = Json::new_gd();
+        if json.parse(text) != Error::OK {
+            return Err(LevelLoadError::Parse {
+                line: json.get_error_line(),
+                message: json.get_error_message().to_string(),
+            });
+        }
+
+        let dict = parse_dictionary(&json.get_data(), "level")?;
+
+        // get level id
+        let id = get_field(&dict, "id").ok_or(LevelLoadError::InvalidField {
+            field: "id".to_string(),
+            expected: "set",
+        })?;
+        let id = parse_string(&id, "id")?;
+        if !is_valid_level_id(&id) {
+            return Err(LevelLoadError::InvalidField {
+                field: "id".to_string(),
+                expected: "a non-empty string of letters, digits, '_' and '-'",
+            });
+        }
+
+        // get next level
+        let next_level = match get_field(&dict, "next_level") {
+            Some(value) => Some(parse_string(&value, "next_level")?),
+            None => None,
+        };
+
+        // get player spawn
+        let player_spawn = match get_field(&dict, "player_spawn") {
+            Some(value) => parse_vector3(&value, "player_spawn")?,
+            None => Vector3::new(0.0, 1.0, 0.0),
+        };
+
+        // level has one objective, by default it is to squash all mobs
+        let mut objectives = parse_list(&dict, "objectives", parse_objective)?;
+        if objectives.len() > 1 {
+            return Err(LevelLoadError::InvalidField {
+                field: "objectives".to_string(),
+                expected: "a list with at most one objective",
+            });
+        }
+        let objective = objectives.pop().unwrap_or(Objective::SquashAll);
+
+        Ok(Self {
+            id,
+            next_level,
+            player_spawn,
+            platforms: parse_list(&dict, "platforms", parse_box)?,
+            walls: parse_list(&dict, "walls", parse_box)?,
+            spikes: parse_list(&dict, "spikes", parse_vector3)?,
+            mobs: parse_list(&dict, "mobs", parse_mob)?,
+            boss: get_field(&dict, "boss")
+                .map(|value| parse_boss(&value, "boss"))
+                .transpose()?,
+            objective,
+        })
+    }
+
+    /// Load level description from file.
+    pub fn from_file(path: &str) -> Result<Self, LevelLoadError> {
+        if !FileAccess::file_exists(path) {
+            return Err(LevelLoadError::FileNotFound(path.to_string()));
+        }
+
+        let text = FileAccess::get_file_as_string(path).to_string();
+        Self::from_json(&text)
+    }
+}
+
+/// Return true if path is a path to level file (not a scene).
+pub fn is_level_file(path: &str) -> bool {
+    path.ends_with(&format!(".{LEVEL_EXTENSION}"))
+}
+
+/// Create a static box (platform or wall).
+pub fn create_box(
+    name: &str,
+    description: &BoxDescription,
+    material: &Option<Gd<Material>>,
+) -> Gd<StaticBody3D> {
+    let mut body = StaticBody3D::new_alloc();
+    body.set_name(name);
+    body.set_collision_layer(WORLD_COLLISION_LAYER);
+    body.set_collision_mask(0);
+
+    // set transform
+    body.set_position(description.position);
+    body.set_rotation_degrees(Vector3::new(0.0, description.rotation, 0.0));
+
+    // create collision shape
+    let mut shape = BoxShape3D::new_gd();
+    shape.set_size(description.size);
+
+    let mut collision = CollisionShape3D::new_alloc();
+    collision.set_shape(&shape);
+    body.add_child(&collision);
+
+    // create mesh
+    let mut mesh = BoxMesh::new_gd();
+    mesh.set_size(description.size);
+
+    let mut mesh_instance = MeshInstance3D::new_alloc();
+    mesh_instance.set_mesh(&mesh);
+    if let Some(material) = material {
+        mesh_instance.set_material_override(material);
+    }
+    body.add_child(&mesh_instance);
+
+    body
+}
+
+/// Create node with given name and add it to parent.
+fn add_group_node(parent: &mut Gd<Node>, name: &str) -> Gd<Node> {
+    let mut node = Node::new_alloc();
+    node.set_name(name);
+    parent.add_child(&node);
+
+    node
+}
+
+/// This class load levels from files and build scenes from them.
+#[derive(GodotClass)]
+#[class(base = RefCounted)]
+pub struct LevelLoader {
+    base: Base<RefCounted>,
+}
+
+#[godot_api]
+impl IRefCounted for LevelLoader {
+    fn init(base: Base<RefCounted>) -> Self {
+        Self { base }
+    }
+}
+
+#[godot_api]
+impl LevelLoader {
+    /// Load level from file and build a scene, return null if level is invalid.
+    #[func]
+    pub fn load_level(path: GString) -> Option<Gd<Node>> {
+        match Self::load(&path.to_string()) {
+            Ok(level) => Some(level.upcast()),
+            Err(error) => {
+                godot_error!("failed to load level '{path}': {error}");
+                None
+            }
+        }
+    }
+
+    /// Return paths to all level files in 'res://levels' and 'user://levels'.
+    #[func]
+    pub fn list_levels() -> PackedStringArray {
+        let mut levels = PackedStringArray::new();
+
+        for directory in LEVEL_DIRECTORIES {
+            // directory is optional
+            if !DirAccess::dir_exists_absolute(directory) {
+                continue;
+            }
+
+            for file in DirAccess::get_files_at(directory).as_slice() {
+                if is_level_file(&file.to_string()) {
+                    levels.push(&format!("{directory}/{file}"));
+                }
+            }
+        }
+
+        levels
+    }
+
+    /// Load level from file and build a scene.
+    pub fn load(path: &str) -> Result<Gd<BaseLevel>, LevelLoadError> {
+        let description = LevelDescription::from_file(path)?;
+        Ok(Self::build(&description))
+    }
+
+    /// Build a scene from level description.
+    pub fn build(description: &LevelDescription) -> Gd<BaseLevel> {
+        let mut level = BaseLevel::new_alloc();
+        level.set_name(&description.id);
+
+        // setup level
+        {
+            let mut level = level.bind_mut();
+            level.level_id = description.id.as_str().into();
+            level.next_level = description.next_level.as_deref().unwrap_or_default().into();
+            level.all_mobs_on_level = description.mobs.len() as i64;
+
+            level.mobs_to_complete = match description.objective {
+                Objective::SquashAll => 0,
+                Objective::SquashCount(count) => count,
+            };
+        }
+
+        let mut root = level.clone().upcast::<Node>();
+
+        // create objects
+        let mut objects = add_group_node(&mut root, "Objects");
+        let platform_material = try_load::<Material>("res://art/materials/platform.tres").ok();
+        let wall_material = try_load::<Material>("res://art/materials/wall.tres").ok();
+
+        let mut platforms = add_group_node(&mut objects, "Platforms");
+        for (i, platform) in description.platforms.iter().enumerate() {
+            platforms.add_child(&create_box(
+                &format!("Platform{i}"),
+                platform,
+                &platform_material,
+            ));
+        }
+
+        platforms.add_to_group(NAVIGATION_GROUP);
+
+        let mut walls = add_group_node(&mut objects, "Walls");
+        for (i, wall) in description.walls.iter().enumerate() {
+            walls.add_child(&create_box(&format!("Wall{i}"), wall, &wall_material));
+        }
+        walls.add_to_group(NAVIGATION_GROUP);
+
+        let spike_scene = load::<PackedScene>("res://scenes/objecst/spike.tscn");
+        let mut spikes = add_group_node(&mut objects, "Spikes");
+        for (i, position) in description.spikes.iter().enumerate() {
+            let mut spike = spike_scene.instantiate_as::<Node3D>();
+            spike.set_name(&format!("Spike{i}"));
+            spike.set_position(*position);
+            spikes.add_child(&spike);
+        }
+
+        // create mobs with paths
+        let mob_scene = load::<PackedScene>("res://scenes/objecst/mob.tscn");
+        let mut mobs = add_group_node(&mut root, "Mobs");
+        for (i, mob_description) in description.mobs.iter().enumerate() {
+            let i = i as i64;
+            let name = |path: String| path.trim_start_matches("Mobs/").to_string();
+
+            let mut mob = mob_scene.instantiate_as::<Mob>();
+            mob.set_name(&name(get_text_mob_name(i)));
+            mob.set_position(mob_description.path[0]);
+            if let Some(archetype) = &mob_description.archetype {
+                mob.bind_mut().archetype_id = archetype.into();
+            }
+            mob.bind_mut().use_navigation = mob_description.navigation;
+            mobs.add_child(&mob);
+
+            // mob with navigation does not need a path
+            if mob_description.navigation {
+                continue;
+            }
+
+            // create path, points are relative to the path node
+            let mut curve = Curve3D::new_gd();
+            for point in &mob_description.path {
+                curve.add_point(*point);
+            }
+            curve.set_closed(mob_description.closed);
+
+            let mut path = Path3D::new_alloc();
+            path.set_name(&name(get_text_mob_path(i)));
+            path.set_curve(&curve);
+
+            let mut follow_path = PathFollow3D::new_alloc();
+            follow_path.set_name("PathFollow3D");
+            path.add_child(&follow_path);
+
+            mobs.add_child(&path);
+        }
+
+        // create boss, spawners of his minions and pool for them
+        if let Some(boss_description) = &description.boss {
+            let mut boss = Boss::new_alloc();
+            boss.set_name("Boss");
+            boss.set_position(boss_description.position);
+            root.add_child(&boss);
+
+            let mut spawners = add_group_node(&mut root, "Spawners");
+            for (i, position) in boss_description.spawners.iter().enumerate() {
+                let mut spawner = Marker3D::new_alloc();
+                spawner.set_name(&format!("Spawner{i}"));
+                spawner.set_position(*position);
+                spawners.add_child(&spawner);
+            }
+
+            let mut pool = MobPool::new_alloc();
+            pool.set_name("MobPool");
+            root.add_child(&pool);
+        }
+
+        // create navigation region, mesh is baked by 'BaseLevel' on ready
+        if description.mobs.iter().any(|mob| mob.navigation) {
+            let mut navigation_mesh = NavigationMesh::new_gd();
+            navigation_mesh.set_parsed_geometry_type(ParsedGeometryType::STATIC_COLLIDERS);
+            navigation_mesh.set_source_geometry_mode(SourceGeometryMode::GROUPS_WITH_CHILDREN);
+            navigation_mesh.set_source_group_name(NAVIGATION_GROUP);
+
+            let mut region = NavigationRegion3D::new_alloc();
+            region.set_name("NavigationRegion3D");
+            region.set_navigation_mesh(&navigation_mesh);
+            root.add_child(&region);
+        }
+
+        // create player
+        let mut player =
+            load::<PackedScene>("res://scenes/objecst/player.tscn").instantiate_as::<Player>();
+        player.set_name("Player");
+        player.set_position(description.player_spawn);
+        player.bind_mut().spawn_coords = description.player_spawn;
+        root.add_child(&player);
+
+        // create camera
+        let mut camera_pivot = Marker3D::new_alloc();
+        camera_pivot.set_name("CameraPivot");
+        camera_pivot.set_rotation(Vector3::new(-FRAC_PI_4, 0.0, 0.0));
+
+        let mut camera = Camera3D::new_alloc();
+        camera.set_name("Camera3D");
+        camera.set_position(Vector3::new(0.0, 0.0, 19.0));
+        camera.set_projection(ProjectionType::ORTHOGONAL);
+        camera.set_size(19.0);
+        camera.set_current(true);
+        camera_pivot.add_child(&camera);
+        root.add_child(&camera_pivot);
+
+        // create light
+        let mut sun = DirectionalLight3D::new_alloc();
+        sun.set_name("Sun");
+        sun.set_rotation(Vector3::new(-FRAC_PI_4 * 3.0, 0.0, 0.0));
+        sun.set_position(Vector3::new(0.0, 37.0, 0.0));
+        sun.set_shadow(true);
+        root.add_child(&sun);
+
+        // create user interface
+        let mut ui = load::<PackedScene>("res://scenes/user_interface.tscn")
+            .instantiate_as::<UserInterface>();
+        ui.set_name("UserInterface");
+        root.add_child(&ui);
+
+        level
+    }
+}
+
+/// Replace current scene with the level from file.
+pub fn change_scene_to_level_file(
+    tree: &mut Gd<SceneTree>,
+    path: &str,
+) -> Result<(), LevelLoadError> {
+    let level = LevelLoader::load(path)?;
+
+    // remove old scene
+    if let Some(mut current_scene) = tree.get_current_scene() {
+        current_scene.queue_free();
+    }
+
+    // add new scene
+    tree.get_root().unwrap().add_child(&level);
+    tree.set_current_scene(&level);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_id_is_checked() {
+        assert!(is_valid_level_id("my_level-2"));
+        assert!(!is_valid_level_id(""));
+        assert!(!is_valid_level_id("../save"));
+        assert!(!is_valid_level_id("levels/my_level"));
+        assert!(!is_valid_level_id("my level"));
+    }
+}
@@ -1,10 +1,25 @@
 //! This module store all logic for levels and 'BaseLevel' class.
 
 // import decencies
-use crate::{mob::Mob, player::Player, ui::UserInterface};
+use crate::{
+    analytics::{EventLog, GameEvent},
+    event_bus::{EventBus, GameState, set_game_state},
+    ghost::{Ghost, GhostRecorder, GhostRun},
+    mob::{Mob, MobMovement, archetype::find_archetype, boss::Boss},
+    mob_pool::MobPool,
+    node_refs::NodeRefs,
+    player::Player,
+    projectile_pool::ProjectilePool,
+    replay::{self, ReplayError},
+    save::{NewRecords, SaveData},
+    stats::RunStats,
+    ui::{SummaryKind, UserInterface},
+};
+#[cfg(feature = "dev-tools")]
+use crate::{console::DevConsole, debug_draw::DebugOverlay, main_scene::Improvements};
 use godot::{
-    classes::{Label, Marker3D, Path3D, PathFollow3D},
-    global::randf_range,
+    classes::{Marker3D, NavigationRegion3D, Path3D, PathFollow3D, object::ConnectFlags},
+    global::Error,
     obj::WithBaseField,
     prelude::*,
 };
@@ -32,17 +47,56 @@ fn get_text_mob_path(mob_num: i64) -> String {
     format!("Mobs/Mob{mob_num}Path")
 }
 
+/// Change scene to level scene or level file.
+pub fn change_scene(tree: &mut Gd<SceneTree>, path: &str) {
+    // levels from external files must be built by loader
+    if loader::is_level_file(path) {
+        if let Err(error) = loader::change_scene_to_level_file(tree, path) {
+            godot_error!("failed to load level '{path}': {error}");
+        }
+        return;
+    }
+
+    let error = tree.change_scene_to_file(path);
+    if error != Error::OK {
+        godot_error!("failed to change scene to '{path}': {error:?}");
+    }
+}
+
 // modules for external level logic
 mod level2;
+pub mod loader;
+pub mod reach;
+mod validator;
 
 /// Base logic for levels.
 #[derive(GodotClass)]
 #[class(base = Node)]
-struct BaseLevel {
+pub struct BaseLevel {
     /// How much mobs on level.
     #[export]
     all_mobs_on_level: i64,
 
+    /// How much mobs must be squashed to complete the level, 0 means all mobs.
+    #[export]
+    mobs_to_complete: i64,
+
+    /// Id of the level in save file.
+    #[export]
+    level_id: GString,
+
+    /// Path to scene of the next level, empty if this level is last.
+    #[export]
+    next_level: GString,
+
+    /// Path to scene with main menu.
+    #[export]
+    menu_scene: GString,
+
+    /// Show ghost of the best run.
+    #[export]
+    show_ghost: bool,
+
     /// Specifies whether to use the standard creep death handling logic,
     /// or use a different logic (implemented in a child class).
     pub use_child_mob_init_logic: bool,
@@ -50,6 +104,57 @@ struct BaseLevel {
     /// How much mobs squashed.
     squashed_mobs: i64,
 
+    /// Statistics of current run.
+    stats: RunStats,
+
+    /// Indicates whether the level is complete or not.
+    is_complete: bool,
+
+    /// Positions of the player for ghost of this run.
+    ghost_recorder: GhostRecorder,
+
+    /// Log of gameplay events.
+    events: EventLog,
+
+    /// Level is played by tests, records, replays, ghosts and
+    /// events are not saved. Must be set before the level enters the tree.
+    pub automated: bool,
+
+    /// The player.
+    #[export]
+    player_path: NodePath,
+    player: OnReady<Gd<Player>>,
+
+    /// User interface.
+    #[export]
+    ui_path: NodePath,
+    ui: OnReady<Gd<UserInterface>>,
+
+    /// Camera follows the player.
+    #[export]
+    camera_pivot_path: NodePath,
+    camera_pivot: OnReady<Gd<Marker3D>>,
+
+    /// Pool of projectiles of shooters, it is created if level has no pool.
+    #[export]
+    projectile_pool_path: NodePath,
+    projectile_pool: OnReady<Gd<ProjectilePool>>,
+
+    /// Boss of the level, optional.
+    #[export]
+    boss_path: NodePath,
+    boss: Option<Gd<Boss>>,
+
+    /// Pool of minions of the boss, optional.
+    #[export]
+    mob_pool_path: NodePath,
+    mob_pool: Option<Gd<MobPool>>,
+
+    /// Parent of points where minions appear, optional.
+    #[export]
+    spawners_path: NodePath,
+    spawners: Option<Gd<Node>>,
+
     base: Base<Node>,
 }
 
@@ -58,8 +163,32 @@ impl INode for BaseLevel {
     fn init(base: Base<Node>) -> Self {
         Self {
             all_mobs_on_level: 0,
-            use_child_mob_init_logic: true,
+            mobs_to_complete: 0,
+            level_id: GString::new(),
+            next_level: GString::new(),
+            menu_scene: "res://scenes/main.tscn".into(),
+            show_ghost: true,
+            use_child_mob_init_logic: false,
             squashed_mobs: 0,
+            stats: RunStats::default(),
+            is_complete: false,
+            ghost_recorder: GhostRecorder::default(),
+            events: EventLog::default(),
+            automated: false,
+            player_path: "Player".into(),
+            player: OnReady::manual(),
+            ui_path: "UserInterface".into(),
+            ui: OnReady::manual(),
+            camera_pivot_path: "CameraPivot".into(),
+            camera_pivot: OnReady::manual(),
+            projectile_pool_path: "ProjectilePool".into(),
+            projectile_pool: OnReady::manual(),
+            boss_path: "Boss".into(),
+            boss: None,
+            mob_pool_path: "MobPool".into(),
+            mob_pool: None,
+            spawners_path: "Spawners".into(),
+            spawners: None,
             base
         }
     }
@@ -67,6 +196,43 @@ impl INode for BaseLevel {
     fn ready(&mut self) {
         godot_print!("ready base level: {}", self.base().get_name());
 
+        // automated runs are not logged
+        if self.automated {
+            self.events.disable();
+        }
+
+        // resolve nodes of the scene and check nodes of the player and the interface
+        // in the same pass, the scene is not started if any node is invalid
+        let mut refs = NodeRefs::new(self.to_gd().upcast());
+        if let Some(player) = refs.resolve(&mut self.player, &self.player_path) {
+            refs.include(player.bind().invalid_refs());
+        }
+        if let Some(ui) = refs.resolve(&mut self.ui, &self.ui_path) {
+            refs.include(ui.bind().invalid_refs());
+        }
+        refs.resolve(&mut self.camera_pivot, &self.camera_pivot_path);
+        let projectile_pool = refs.resolve_optional(&self.projectile_pool_path);
+        self.boss = refs.resolve_optional(&self.boss_path);
+        self.mob_pool = refs.resolve_optional(&self.mob_pool_path);
+        self.spawners = refs.resolve_optional(&self.spawners_path);
+        if !refs.finish() {
+            return;
+        }
+
+        // shooters fire projectiles from the pool, it is created if level has no pool
+        let projectile_pool = projectile_pool.unwrap_or_else(|| {
+            let mut pool = ProjectilePool::new_alloc();
+            pool.set_name("ProjectilePool");
+            self.base_mut().add_child(&pool);
+            pool
+        });
+        self.projectile_pool.init(projectile_pool);
+
+        // record or play input of the run
+        let level_id = self.level_id.to_string();
+        let seed = replay::start_run(&mut self.player.bind_mut(), &level_id, None);
+        self.events.log(GameEvent::RunStarted { level_id, seed });
+
         // if mobs init in this class, then init mobs
         if !self.use_child_mob_init_logic {
             // connect 'squashed' signal from all mobs
@@ -76,10 +242,21 @@ impl INode for BaseLevel {
 
                 // check mob is exits or not
                 if let Some(mut mob) = mob {
-                    // if mob exits then connect signal
+                    // if mob exits then connect signal, the last squash completes
+                    // the level and it is emitted inside physics of the player
                     mob.signals()
                         .squashed()
-                        .connect_obj(self, Self::on_mob_squashed);
+                        .connect_builder()
+                        .object(&self.to_gd())
+                        .method_mut(Self::on_mob_squashed)
+                        .flags(ConnectFlags::DEFERRED)
+                        .done();
+
+                    // shooters fire projectiles from the pool
+                    let projectile_pool = self.projectile_pool.clone();
+                    mob.signals()
+                        .projectile_requested()
+                        .connect_obj(&projectile_pool, ProjectilePool::on_projectile_requested);
 
                     //* The following code is needed to be able to move along a given path!
 
@@ -91,98 +268,481 @@ impl INode for BaseLevel {
                     // get path
                     let path = self.base().try_get_node_as::<Path3D>(&get_text_mob_path(i));
 
-                    // get speed
-                    let follow_speed = randf_range(0.1, 0.34);
-
-                    // init mob
-                    mob.bind_mut().initialize(follow_path, path, follow_speed);
+                    // init mob, mob without path can chase the player by navigation
+                    let movement = if path.is_none() && mob.bind().use_navigation {
+                        MobMovement::Navigate
+                    } else {
+                        MobMovement::from_path(follow_path, path)
+                    };
+                    mob.bind_mut().initialize(movement);
                 }
             }
         }
 
-        // connect 'hit' signal from player
-        self.base()
-            .get_node_as::<Player>("Player")
-            .signals()
-            .hit()
-            .connect_obj(self, Self::on_player_hit);
+        // bake navigation mesh if it is not baked in editor
+        self.bake_navigation();
 
-        // ! FOR DEVELOP!
-        self.base()
-            .get_node_as::<Player>("Player")
-            .bind_mut()
-            .alive();
+        // connect signals from the boss and his minions
+        self.connect_boss();
 
-        self.base()
-            .get_node_as::<UserInterface>("UserInterface")
-            .bind_mut()
-            .start_new_game();
+        // subscribe to death of the player, it is emitted inside physics of the player
+        EventBus::singleton()
+            .signals()
+            .player_hit()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_player_hit)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        // connect signals from summary screen
+        let mut ui = self.ui.clone();
+
+        ui.signals()
+            .retry_requested()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::restart_level)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        ui.signals()
+            .next_level_requested()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_next_level_requested)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        ui.signals()
+            .menu_requested()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_menu_requested)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        // attach developer console and debug overlay in debug builds
+        #[cfg(feature = "dev-tools")]
+        self.attach_dev_tools();
 
         // alive player
-        self.base()
-            .get_node_as::<Player>("Player")
-            .bind_mut()
-            .alive();
+        self.player.bind_mut().alive();
 
-        // ! NEED ONLY FOR DEVELOP LEVELS AND FAST TESTING
-        self.base()
-            .get_node_as::<UserInterface>("UserInterface")
-            .bind_mut()
-            .start_new_game();
+        // race with the best run
+        self.load_ghost();
+        if let Some(mut ghost) = self.ghost() {
+            ghost.bind_mut().start();
+        }
+
+        // level starts without menu
+        set_game_state(GameState::Playing);
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        // update statistics while level in progress, in physics ticks for replays
+        let player = self.player.clone();
+        if player.bind().is_alive() && !self.is_complete {
+            self.stats.update(delta);
+            self.ghost_recorder.record(delta, player.get_position());
+        }
     }
 
     fn process(&mut self, _delta: f64) {
-        self.base()
-            .get_node_as::<Marker3D>("CameraPivot")
-            .set_position(self.base().get_node_as::<Player>("Player").get_position());
+        let player = self.player.clone();
+        self.camera_pivot.set_position(player.get_position());
     }
 }
 
 #[godot_api]
 impl BaseLevel {
-    /// Update 'squashed_mobs' on mob squashed and play sound if all mobs in level squashed
-    fn on_mob_squashed(&mut self) {
+    /// Update 'squashed_mobs' on mob squashed and show summary if all mobs in level squashed
+    fn on_mob_squashed(&mut self, score_value: i64) {
         self.squashed_mobs += 1;
+        self.on_squashed(score_value);
 
-        if self.squashed_mobs == self.all_mobs_on_level {
-            // TODO
+        // level with boss is complete only when the boss is defeated
+        if self.boss.is_none() && self.squashed_mobs == self.mobs_to_complete() {
             godot_print!("all mob squashed!");
+            self.complete_level();
+        }
+    }
+
+    /// Update statistics and log squash, the creep is squashed under the player.
+    fn on_squashed(&mut self, score_value: i64) {
+        self.stats.on_mob_squashed(score_value);
+
+        let position = self.player.get_global_position();
+        self.events.log(GameEvent::Squash {
+            position,
+            combo: self.stats.combo(),
+            score: score_value,
+        });
 
-            //* for visual output, only on develop!
-            let mut label = self
-                .base()
-                .get_node_as::<UserInterface>("UserInterface")
-                .get_node_as::<Label>("Improvement");
+        // update score
+        EventBus::singleton()
+            .signals()
+            .score_changed()
+            .emit(self.stats.score);
+    }
 
-            label.show();
-            label.set_text("End of level!");
+    /// Save records and show summary of complete level.
+    fn complete_level(&mut self) {
+        self.is_complete = true;
+        self.events.log(GameEvent::LevelComplete {
+            time: self.stats.time_survived,
+            score: self.stats.score,
+        });
+        self.events.log(GameEvent::RunEnded {
+            completed: true,
+            stats: self.stats.clone(),
+        });
+
+        // save replay and records of the run, automated runs don't replace them
+        let level_id = self.level_id.to_string();
+        let records = if self.automated {
+            NewRecords::default()
+        } else {
+            replay::finish_run(&mut self.player.bind_mut());
+            SaveData::load().submit_run(&level_id, &self.stats, true)
+        };
+
+        // fastest run becomes the ghost
+        let run = self.ghost_recorder.finish(&level_id);
+        if records.time {
+            if let Err(error) = run.save_best() {
+                godot_error!("failed to save ghost: {error}");
+            }
+            self.set_ghost_run(run);
         }
+
+        // show summary
+        set_game_state(GameState::LevelComplete);
+        let has_next_level = !self.next_level.is_empty();
+        self.ui.bind_mut().show_summary(
+            SummaryKind::LevelComplete,
+            &self.stats,
+            records,
+            has_next_level,
+        );
     }
 
-    /// auto alive for player,
-    // ! ONLY FOR DEVELOP!
+    /// Ghost of the best run, 'None' if ghost is disabled or not created.
+    fn ghost(&self) -> Option<Gd<Ghost>> {
+        self.base().try_get_node_as::<Ghost>("Ghost")
+    }
+
+    /// Load ghost of the best run if it exists.
+    fn load_ghost(&mut self) {
+        match GhostRun::load_best(&self.level_id.to_string()) {
+            Ok(run) => self.set_ghost_run(run),
+            Err(ReplayError::FileNotFound(_)) => {}
+            Err(error) => godot_warn!("failed to load ghost: {error}"),
+        }
+    }
+
+    /// Set run for the ghost, ghost is created if need.
+    fn set_ghost_run(&mut self, run: GhostRun) {
+        if !self.show_ghost {
+            return;
+        }
+
+        let mut ghost = match self.ghost() {
+            Some(ghost) => ghost,
+            None => {
+                let mut ghost = Ghost::new_alloc();
+                ghost.set_name("Ghost");
+                self.base_mut().add_child(&ghost);
+                ghost
+            }
+        };
+        ghost.bind_mut().set_run(run);
+    }
+
+    /// Connect signals from the boss and the pool of his minions, if level has them.
+    fn connect_boss(&mut self) {
+        let Some(mut boss) = self.boss.clone() else {
+            return;
+        };
+
+        boss.signals()
+            .health_changed()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_boss_health_changed)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        boss.signals()
+            .summon_requested()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_summon_requested)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        boss.signals()
+            .defeated()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_boss_defeated)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        // minions give score, but they are not needed to complete the level
+        if let Some(pool) = self.mob_pool.clone() {
+            pool.signals()
+                .mob_squashed()
+                .connect_builder()
+                .object(&self.to_gd())
+                .method_mut(Self::on_minion_squashed)
+                .flags(ConnectFlags::DEFERRED)
+                .done();
+
+            let projectile_pool = self.projectile_pool.clone();
+            pool.signals()
+                .projectile_requested()
+                .connect_obj(&projectile_pool, ProjectilePool::on_projectile_requested);
+        }
+
+        // show health bar of the boss
+        boss.bind_mut().reset();
+    }
+
+    /// Update health bar of the boss.
+    fn on_boss_health_changed(&mut self, health: i64, max_health: i64) {
+        self.ui
+            .bind_mut()
+            .set_boss_health(Some((health, max_health)));
+    }
+
+    /// Complete the level if the boss is defeated.
+    fn on_boss_defeated(&mut self, score_value: i64) {
+        self.on_squashed(score_value);
+
+        if !self.is_complete {
+            godot_print!("boss defeated!");
+            self.complete_level();
+        }
+    }
+
+    /// Update statistics if minion of the boss squashed.
+    fn on_minion_squashed(&mut self, score_value: i64) {
+        self.on_squashed(score_value);
+    }
+
+    /// Spawn minions of the boss (or creeps from the developer console)
+    /// on spawners of the level, they run to the player.
+    fn on_summon_requested(&mut self, archetype_id: GString, count: i64) {
+        let Some(mut pool) = self.mob_pool.clone() else {
+            godot_warn!("can't spawn minions: level has no mob pool");
+            return;
+        };
+        let Some(archetype) = find_archetype(&archetype_id.to_string()) else {
+            godot_warn!("unknown mob archetype '{archetype_id}'");
+            return;
+        };
+        if archetype.is_splitter() {
+            godot_warn!("splitter creeps are not supported in levels");
+            return;
+        }
+
+        // minions appear near spawners, or near the boss if level has no spawners
+        let mut spawn_points: Vec<Vector3> = self
+            .spawners
+            .as_ref()
+            .map(|spawners| {
+                spawners
+                    .get_children()
+                    .iter_shared()
+                    .filter_map(|child| child.try_cast::<Node3D>().ok())
+                    .map(|spawner| spawner.get_global_position())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if spawn_points.is_empty() {
+            spawn_points.extend(self.boss.clone().map(|boss| boss.get_global_position()));
+        }
+
+        let player_position = self.player.get_position();
+        // negative amount from a signal spawns nothing
+        for i in 0..count.max(0) as usize {
+            let Some(point) = spawn_points.get(i % spawn_points.len().max(1)) else {
+                return;
+            };
+
+            // skip spawn if too many mobs alive
+            let Some(mut mob) = pool.bind_mut().acquire() else {
+                return;
+            };
+
+            mob.bind_mut().set_archetype(archetype);
+            mob.set_position(*point);
+            mob.bind_mut().initialize(MobMovement::AimAt {
+                target: player_position,
+                jitter: 0.3,
+            });
+            mob.bind_mut().alive();
+        }
+    }
+
+    /// Bake navigation mesh of 'NavigationRegion3D' if level has it and mesh is empty.
+    fn bake_navigation(&mut self) {
+        let Some(mut region) = self
+            .base()
+            .try_get_node_as::<NavigationRegion3D>("NavigationRegion3D")
+        else {
+            return;
+        };
+
+        let is_baked = region
+            .get_navigation_mesh()
+            .is_some_and(|mesh| mesh.get_polygon_count() > 0);
+        if !is_baked {
+            region.bake_navigation_mesh();
+        }
+    }
+
+    /// How much mobs must be squashed to complete the level.
+    fn mobs_to_complete(&self) -> i64 {
+        if self.mobs_to_complete > 0 {
+            self.mobs_to_complete.min(self.all_mobs_on_level)
+        } else {
+            self.all_mobs_on_level
+        }
+    }
+
+    /// Show summary screen if player die.
     fn on_player_hit(&mut self) {
-        self.base()
-            .get_node_as::<Player>("Player")
+        // the level is already complete, death is not important
+        if self.is_complete {
+            return;
+        }
+
+        // update statistics
+        let mut player = self.player.clone();
+        let cause = player.bind().death_cause;
+        self.stats.death_cause = Some(cause);
+        self.events.log(GameEvent::Death {
+            position: player.get_global_position(),
+            cause,
+        });
+        self.events.log(GameEvent::RunEnded {
+            completed: false,
+            stats: self.stats.clone(),
+        });
+
+        // save replay and records of the run, automated runs don't replace them
+        let records = if self.automated {
+            NewRecords::default()
+        } else {
+            replay::finish_run(&mut player.bind_mut());
+            SaveData::load().submit_run(&self.level_id.to_string(), &self.stats, false)
+        };
+
+        // show summary
+        set_game_state(GameState::GameOver);
+        self.ui
             .bind_mut()
-            .alive();
+            .show_summary(SummaryKind::GameOver, &self.stats, records, false);
+    }
+
+    /// Alive player and all mobs to play level from start.
+    fn restart_level(&mut self) {
+        // new run is recorded from the start
+        let level_id = self.level_id.to_string();
+        let mut player = self.player.clone();
+        let seed = replay::start_run(&mut player.bind_mut(), &level_id, None);
+        self.events.log(GameEvent::RunStarted { level_id, seed });
+        player.bind_mut().alive();
 
         // alive all mobs
         for i in 0..self.all_mobs_on_level {
-            let mob = self.base().try_get_node_as::<Mob>(&format!("Mobs/Mob{i}"));
+            let mob = self.base().try_get_node_as::<Mob>(&get_text_mob_name(i));
 
             if let Some(mut mob) = mob {
                 mob.bind_mut().alive();
             }
         }
 
-        // set squashed mobs to zero
+        // restore the boss and remove his minions
+        if let Some(mut boss) = self.boss.clone() {
+            boss.bind_mut().reset();
+        }
+        if let Some(mut pool) = self.mob_pool.clone() {
+            pool.bind_mut().release_all();
+        }
+        self.projectile_pool.bind_mut().release_all();
+
+        // set squashed mobs and statistics to zero
         self.squashed_mobs = 0;
+        self.stats = RunStats::default();
+        self.is_complete = false;
+
+        // record new run and start the ghost again
+        self.ghost_recorder.clear();
+        if let Some(mut ghost) = self.ghost() {
+            ghost.bind_mut().start();
+        }
+
+        // reset UI
+        set_game_state(GameState::Playing);
+    }
+
+    /// Change scene to next level.
+    fn on_next_level_requested(&mut self) {
+        let next_level = self.next_level.clone();
+        self.change_scene(next_level);
+    }
+
+    /// Change scene to main menu.
+    fn on_menu_requested(&mut self) {
+        let menu_scene = self.menu_scene.clone();
+        self.change_scene(menu_scene);
+    }
+
+    /// Change current scene to scene or level from file.
+    fn change_scene(&mut self, path: GString) {
+        change_scene(&mut self.base().get_tree().unwrap(), &path.to_string());
+    }
+}
+
+/// Development tools of levels.
+#[cfg(feature = "dev-tools")]
+impl BaseLevel {
+    /// Return true if the level is complete.
+    pub fn is_complete(&self) -> bool {
+        self.is_complete
+    }
+
+    /// Attach developer console and debug overlay,
+    /// commands of console which need the level are sent by signals.
+    fn attach_dev_tools(&mut self) {
+        let console = DevConsole::new_alloc();
+        console
+            .signals()
+            .give_requested()
+            .connect_obj(&self.to_gd(), Self::on_give_requested);
+        console
+            .signals()
+            .spawn_requested()
+            .connect_obj(&self.to_gd(), Self::on_summon_requested);
+        self.base_mut().add_child(&console);
+        self.base_mut().add_child(&DebugOverlay::new_alloc());
+    }
+
+    /// Give improvement requested by the developer console.
+    fn on_give_requested(&mut self, item: GString) {
+        // levels have no slowdown of creeps, only the shield
+        if item != "shield" {
+            godot_warn!("improvement '{item}' is not supported in levels");
+            return;
+        }
 
-        // hide label
-        self.base()
-            .get_node_as::<UserInterface>("UserInterface")
-            .get_node_as::<Label>("Improvement")
-            .hide();
+        // the player and UI activate the shield themselves
+        EventBus::singleton()
+            .signals()
+            .power_up_changed()
+            .emit(Improvements::PlayerShield);
     }
 }
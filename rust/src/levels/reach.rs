@@ -0,0 +1,113 @@
+//! This module store math for check which surfaces of level the player
+//! can reach by walking and jumping.
+
+use godot::prelude::*;
+use std::collections::VecDeque;
+
+/// How far (in m) the point can be above the surface to stand on it.
+const STAND_TOLERANCE: f32 = 1.5;
+
+/// This struct describe a horizontal surface (top of platform or ground).
+#[derive(Clone, Copy)]
+pub struct Surface {
+    /// Minimal corner of the surface (x and z).
+    pub min: Vector2,
+
+    /// Maximal corner of the surface (x and z).
+    pub max: Vector2,
+
+    /// Height of the surface.
+    pub height: f32,
+}
+
+impl Surface {
+    /// Create surface from top of axis aligned box.
+    pub fn from_aabb(aabb: Aabb) -> Self {
+        let end = aabb.end();
+
+        Self {
+            min: Vector2::new(aabb.position.x, aabb.position.z),
+            max: Vector2::new(end.x, end.z),
+            height: end.y,
+        }
+    }
+
+    /// Return true if point (x and z) is above or below the surface.
+    pub fn contains(&self, point: Vector3) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.z >= self.min.y
+            && point.z <= self.max.y
+    }
+
+    /// Horizontal distance between edges of two surfaces, 0 if they overlap.
+    pub fn gap_to(&self, other: &Surface) -> f32 {
+        let gap_x = (other.min.x - self.max.x)
+            .max(self.min.x - other.max.x)
+            .max(0.0);
+        let gap_z = (other.min.y - self.max.y)
+            .max(self.min.y - other.max.y)
+            .max(0.0);
+
+        Vector2::new(gap_x, gap_z).length()
+    }
+}
+
+/// This struct store how high and how far the player can jump.
+#[derive(Clone, Copy)]
+pub struct JumpAbility {
+    /// Maximum height of jump, in m.
+    pub height: f32,
+
+    /// Maximum distance of jump on the same height, in m.
+    pub distance: f32,
+}
+
+impl JumpAbility {
+    /// Calculate jump ability from player parameters.
+    pub fn new(jump_impulse: f64, fall_acceleration: f64, speed: f64) -> Self {
+        let height = jump_impulse * jump_impulse / (2.0 * fall_acceleration);
+        let air_time = 2.0 * jump_impulse / fall_acceleration;
+
+        Self {
+            height: height as f32,
+            distance: (speed * air_time) as f32,
+        }
+    }
+
+    /// Return true if player can get from one surface to another.
+    pub fn can_reach(&self, from: &Surface, to: &Surface) -> bool {
+        to.height - from.height <= self.height && from.gap_to(to) <= self.distance
+    }
+}
+
+/// Get index of the highest surface under the point.
+pub fn surface_under(surfaces: &[Surface], point: Vector3) -> Option<usize> {
+    surfaces
+        .iter()
+        .enumerate()
+        .filter(|(_, surface)| {
+            surface.contains(point) && surface.height <= point.y + STAND_TOLERANCE
+        })
+        .max_by(|(_, a), (_, b)| a.height.total_cmp(&b.height))
+        .map(|(i, _)| i)
+}
+
+/// Find all surfaces reachable from the start surface.
+/// Return vector where 'true' means the surface with same index is reachable.
+pub fn reachable_surfaces(surfaces: &[Surface], start: usize, jump: JumpAbility) -> Vec<bool> {
+    let mut reachable = vec![false; surfaces.len()];
+    let mut queue = VecDeque::from([start]);
+    reachable[start] = true;
+
+    while let Some(current) = queue.pop_front() {
+        for (next, surface) in surfaces.iter().enumerate() {
+            if !reachable[next] && jump.can_reach(&surfaces[current], surface) {
+                reachable[next] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    reachable
+}
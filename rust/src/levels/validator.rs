@@ -0,0 +1,451 @@
+//! This module store a validator for levels. Validator find structural
+//! errors of levels (missing nodes, wrong mob count, unreachable platforms,
+//! spikes on spawn) without playing them.
+//!
+//! Validator can be started headless and return non-zero exit code if any
+//! level has errors:
+//! ```sh
+//! godot --headless --path godot res://scenes/tools/validate_levels.tscn
+//! godot --headless --path godot res://scenes/tools/validate_levels.tscn -- res://scenes/levels/level_2.tscn
+//! ```
+
+use super::{
+    BaseLevel, get_text_mob_follow_path, get_text_mob_name, get_text_mob_path,
+    loader::{LevelLoader, is_level_file},
+    reach::{JumpAbility, Surface, reachable_surfaces, surface_under},
+};
+use crate::{
+    mob::{Mob, archetype::find_archetype, boss::Boss},
+    mob_pool::MobPool,
+    node_refs::find_node,
+    player::Player,
+    ui::UserInterface,
+};
+use godot::{
+    classes::{
+        BoxShape3D, CollisionObject3D, CollisionShape3D, DirAccess, INode, Marker3D,
+        NavigationRegion3D, Os, Path3D, PathFollow3D, StaticBody3D,
+    },
+    obj::WithBaseField,
+    prelude::*,
+};
+use std::fmt;
+
+/// Directory with hand-made levels.
+const SCENE_LEVELS_DIRECTORY: &str = "res://scenes/levels";
+
+/// Collision layer of enemies ("enemies" in project settings).
+const ENEMY_COLLISION_LAYER: u32 = 2;
+
+/// Minimal horizontal distance (in m) between player spawn and spikes.
+const SPAWN_SAFE_RADIUS: f32 = 2.0;
+
+/// This struct store one problem found in level.
+pub struct LevelIssue {
+    /// Path to the node with problem.
+    pub node_path: String,
+
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for LevelIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.node_path, self.message)
+    }
+}
+
+/// This struct collect issues of one level.
+struct Report<'a> {
+    level: &'a Gd<Node>,
+    issues: Vec<LevelIssue>,
+}
+
+impl Report<'_> {
+    /// Add issue for node with path relative to the level.
+    fn add(&mut self, path: &str, message: impl Into<String>) {
+        let node_path = if path == "." {
+            self.level.get_name().to_string()
+        } else {
+            format!("{}/{path}", self.level.get_name())
+        };
+
+        self.issues.push(LevelIssue {
+            node_path,
+            message: message.into(),
+        });
+    }
+
+    /// Add issue for node from level.
+    fn add_for_node(&mut self, node: &Gd<Node>, message: impl Into<String>) {
+        let path = self.level.get_path_to(node).to_string();
+        self.add(&path, message);
+    }
+
+    /// Get node and check his class, add issue if node is missing or has wrong class.
+    fn require<T: GodotClass + Inherits<Node>>(&mut self, path: &str) -> Option<Gd<T>> {
+        match find_node::<T>(self.level, path) {
+            Ok(node) => Some(node),
+            Err(message) => {
+                self.add(path, message);
+                None
+            }
+        }
+    }
+}
+
+/// Collect all descendants of the node.
+fn collect_descendants(node: &Gd<Node>, descendants: &mut Vec<Gd<Node>>) {
+    for child in node.get_children().iter_shared() {
+        collect_descendants(&child, descendants);
+        descendants.push(child);
+    }
+}
+
+/// Calculate transform of the node relative to the level,
+/// works for levels which are not added to scene tree.
+fn level_transform(level: &Gd<Node>, node: &Gd<Node3D>) -> Transform3D {
+    let mut transform = node.get_transform();
+    let mut parent = node.get_parent();
+
+    while let Some(current) = parent {
+        if current == *level {
+            break;
+        }
+
+        if let Ok(current) = current.clone().try_cast::<Node3D>() {
+            transform = current.get_transform() * transform;
+        }
+
+        parent = current.get_parent();
+    }
+
+    transform
+}
+
+/// Return true if body is a platform or ground (not a wall).
+fn is_walkable(body: &Gd<StaticBody3D>) -> bool {
+    let name = body.get_name().to_string();
+    let parent_name = body
+        .get_parent()
+        .map(|parent| parent.get_name().to_string())
+        .unwrap_or_default();
+
+    name.starts_with("Ground")
+        || name.starts_with("Platform")
+        || parent_name.starts_with("Platform")
+}
+
+/// Get top surfaces of all box shapes of the body.
+fn body_surfaces(level: &Gd<Node>, body: &Gd<StaticBody3D>) -> Vec<Surface> {
+    let mut surfaces = Vec::new();
+
+    for child in body.get_children().iter_shared() {
+        let Ok(collision) = child.try_cast::<CollisionShape3D>() else {
+            continue;
+        };
+        let Some(Ok(shape)) = collision
+            .get_shape()
+            .map(|shape| shape.try_cast::<BoxShape3D>())
+        else {
+            continue;
+        };
+
+        // transform corners of the box to level space
+        let transform = level_transform(level, &collision.clone().upcast());
+        let half = shape.get_size() / 2.0;
+        let mut aabb = Aabb::new(transform * -half, Vector3::ZERO);
+
+        for x in [-half.x, half.x] {
+            for y in [-half.y, half.y] {
+                for z in [-half.z, half.z] {
+                    aabb = aabb.expand(transform * Vector3::new(x, y, z));
+                }
+            }
+        }
+
+        surfaces.push(Surface::from_aabb(aabb));
+    }
+
+    surfaces
+}
+
+/// Check a level and return all found issues, level must not be added to scene tree.
+pub fn validate_level(level: &Gd<Node>) -> Vec<LevelIssue> {
+    let mut report = Report {
+        level,
+        issues: Vec::new(),
+    };
+
+    // check root
+    let Ok(base_level) = level.clone().try_cast::<BaseLevel>() else {
+        report.add(
+            ".",
+            format!("root must be 'BaseLevel', found '{}'", level.get_class()),
+        );
+        return report.issues;
+    };
+
+    let (all_mobs_on_level, mobs_to_complete) = {
+        let base_level = base_level.bind();
+        (base_level.all_mobs_on_level, base_level.mobs_to_complete)
+    };
+
+    // check nodes required by 'BaseLevel'
+    let player = report.require::<Player>("Player");
+    report.require::<UserInterface>("UserInterface");
+    report.require::<Marker3D>("CameraPivot");
+    let mobs = report.require::<Node>("Mobs");
+
+    // check mobs and their paths
+    let mut needs_navigation = false;
+    for i in 0..all_mobs_on_level {
+        let mob = report.require::<Mob>(&get_text_mob_name(i));
+
+        // levels don't spawn children of splitters
+        let archetype_id = mob.as_ref().map(|mob| mob.bind().archetype_id.to_string());
+        if archetype_id
+            .and_then(|id| find_archetype(&id))
+            .is_some_and(|archetype| archetype.is_splitter())
+        {
+            report.add(
+                &get_text_mob_name(i),
+                "splitter creeps are not supported in levels",
+            );
+        }
+
+        // mob which uses navigation can have no path
+        let uses_navigation = mob.is_some_and(|mob| mob.bind().use_navigation);
+        needs_navigation |= uses_navigation;
+        if uses_navigation && level.get_node_or_null(&get_text_mob_path(i)).is_none() {
+            continue;
+        }
+
+        if let Some(path) = report.require::<Path3D>(&get_text_mob_path(i)) {
+            let point_count = path.get_curve().map_or(0, |curve| curve.get_point_count());
+            if point_count < 2 {
+                report.add(&get_text_mob_path(i), "path must have at least 2 points");
+            }
+        }
+
+        report.require::<PathFollow3D>(&get_text_mob_follow_path(i));
+    }
+
+    // check amount of mobs
+    if let Some(mobs) = mobs {
+        let mob_count = mobs
+            .get_children()
+            .iter_shared()
+            .filter(|child| child.clone().try_cast::<Mob>().is_ok())
+            .count() as i64;
+
+        if mob_count != all_mobs_on_level {
+            report.add(
+                ".",
+                format!(
+                    "'all_mobs_on_level' is {all_mobs_on_level}, but 'Mobs' contain {mob_count} mobs"
+                ),
+            );
+        }
+    }
+
+    if needs_navigation {
+        report.require::<NavigationRegion3D>("NavigationRegion3D");
+    }
+
+    // boss summons minions from the pool
+    if level.get_node_or_null("Boss").is_some() {
+        report.require::<Boss>("Boss");
+        report.require::<MobPool>("MobPool");
+    }
+
+    if mobs_to_complete > all_mobs_on_level {
+        report.add(
+            ".",
+            format!("'mobs_to_complete' ({mobs_to_complete}) is more than mobs on level ({all_mobs_on_level})"),
+        );
+    }
+
+    // next checks need a player
+    let Some(player) = player else {
+        return report.issues;
+    };
+    let (spawn, jump) = {
+        let player = player.bind();
+        (
+            player.spawn_coords,
+            JumpAbility::new(player.jump_impulse, player.fall_acceleration, player.speed),
+        )
+    };
+
+    let mut descendants = Vec::new();
+    collect_descendants(level, &mut descendants);
+
+    // check spikes near player spawn
+    for node in &descendants {
+        let Ok(body) = node.clone().try_cast::<CollisionObject3D>() else {
+            continue;
+        };
+        if body.get_collision_layer() & ENEMY_COLLISION_LAYER == 0
+            || body.clone().try_cast::<Mob>().is_ok()
+        {
+            continue;
+        }
+
+        let position = level_transform(level, &body.upcast()).origin;
+        let horizontal_distance = Vector2::new(position.x - spawn.x, position.z - spawn.z).length();
+
+        if horizontal_distance < SPAWN_SAFE_RADIUS
+            && (position.y - spawn.y).abs() < SPAWN_SAFE_RADIUS
+        {
+            report.add_for_node(node, "spike overlaps player spawn");
+        }
+    }
+
+    // check that all platforms are reachable
+    let mut surfaces = Vec::new();
+    let mut owners = Vec::new();
+    for node in &descendants {
+        let Ok(body) = node.clone().try_cast::<StaticBody3D>() else {
+            continue;
+        };
+        if !is_walkable(&body) {
+            continue;
+        }
+
+        for surface in body_surfaces(level, &body) {
+            surfaces.push(surface);
+            owners.push(node.clone());
+        }
+    }
+
+    let Some(start) = surface_under(&surfaces, spawn) else {
+        report.add("Player", "player spawn is not above any platform or ground");
+        return report.issues;
+    };
+
+    for (reachable, owner) in reachable_surfaces(&surfaces, start, jump)
+        .iter()
+        .zip(&owners)
+    {
+        if !reachable {
+            report.add_for_node(owner, "platform is unreachable from player spawn");
+        }
+    }
+
+    report.issues
+}
+
+/// Load level from scene or level file.
+fn load_level(path: &str) -> Result<Gd<Node>, String> {
+    if is_level_file(path) {
+        return LevelLoader::load(path)
+            .map(|level| level.upcast())
+            .map_err(|error| error.to_string());
+    }
+
+    try_load::<PackedScene>(path)
+        .map_err(|error| error.to_string())?
+        .instantiate()
+        .ok_or_else(|| "failed to instantiate scene".to_string())
+}
+
+/// Get paths of all levels in project.
+fn all_levels() -> Vec<String> {
+    let mut levels: Vec<String> = DirAccess::get_files_at(SCENE_LEVELS_DIRECTORY)
+        .as_slice()
+        .iter()
+        .map(|file| file.to_string())
+        .filter(|file| file.ends_with(".tscn"))
+        .map(|file| format!("{SCENE_LEVELS_DIRECTORY}/{file}"))
+        .collect();
+
+    levels.extend(
+        LevelLoader::list_levels()
+            .as_slice()
+            .iter()
+            .map(|path| path.to_string()),
+    );
+    levels
+}
+
+/// This class validate levels and quit with exit code 1 if any level is broken.
+#[derive(GodotClass)]
+#[class(base = Node)]
+pub struct LevelValidatorRunner {
+    /// Levels to validate, if empty then all levels are validated.
+    #[export]
+    levels: PackedStringArray,
+
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for LevelValidatorRunner {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            levels: PackedStringArray::new(),
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        // levels from command line have priority
+        let mut levels: Vec<String> = Os::singleton()
+            .get_cmdline_user_args()
+            .as_slice()
+            .iter()
+            .map(|path| path.to_string())
+            .collect();
+
+        if levels.is_empty() {
+            levels = self
+                .levels
+                .as_slice()
+                .iter()
+                .map(|path| path.to_string())
+                .collect();
+        }
+        if levels.is_empty() {
+            levels = all_levels();
+        }
+
+        // validate all levels
+        let mut broken_levels = 0;
+        for path in &levels {
+            let issues = match load_level(path) {
+                Ok(level) => {
+                    let issues = validate_level(&level);
+                    level.free();
+                    issues
+                }
+                Err(error) => {
+                    godot_print!("[FAIL] {path}: {error}");
+                    broken_levels += 1;
+                    continue;
+                }
+            };
+
+            if issues.is_empty() {
+                godot_print!("[ OK ] {path}");
+            } else {
+                godot_print!("[FAIL] {path}");
+                for issue in &issues {
+                    godot_print!("       {issue}");
+                }
+                broken_levels += 1;
+            }
+        }
+
+        godot_print!("{} levels checked, {broken_levels} broken", levels.len());
+
+        // exit with error if any level is broken
+        let exit_code = if broken_levels == 0 { 0 } else { 1 };
+        self.base()
+            .get_tree()
+            .unwrap()
+            .quit_ex()
+            .exit_code(exit_code)
+            .done();
+    }
+}
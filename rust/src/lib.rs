@@ -1,11 +1,47 @@
+use godot::init::InitLevel;
 use godot::prelude::{gdextension, ExtensionLibrary};
 
+mod analytics;
+mod arena;
+#[cfg(feature = "dev-tools")]
+mod bot;
+mod command_line;
+#[cfg(feature = "dev-tools")]
+mod console;
+#[cfg(feature = "dev-tools")]
+mod debug_draw;
+mod difficulty;
+mod event_bus;
+mod ghost;
+#[cfg(feature = "dev-tools")]
+mod harness;
 mod levels;
 mod main_scene;
 mod mob;
+mod mob_pool;
+mod node_refs;
 mod player;
+mod projectile;
+mod projectile_pool;
+mod replay;
+mod save;
+mod spawn_director;
+mod stats;
+mod tutorial;
 mod ui;
 
 struct SquashTheCreeps;
 #[gdextension]
-unsafe impl ExtensionLibrary for SquashTheCreeps {}
+unsafe impl ExtensionLibrary for SquashTheCreeps {
+    fn on_level_init(level: InitLevel) {
+        if level == InitLevel::Scene {
+            event_bus::register();
+        }
+    }
+
+    fn on_level_deinit(level: InitLevel) {
+        if level == InitLevel::Scene {
+            event_bus::unregister();
+        }
+    }
+}
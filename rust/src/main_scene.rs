@@ -1,35 +1,138 @@
 //! In this file stored all logic, classes, struct and enums for Main scene.
 
-use crate::{mob::Mob, player::Player, ui::UserInterface};
+use crate::{
+    analytics::{EventLog, GameEvent},
+    arena::{ProceduralArena, closed_curve},
+    difficulty::DifficultyController,
+    event_bus::{EventBus, GameState, set_game_state},
+    mob::{
+        MobMovement,
+        archetype::{MobArchetype, default_archetype, find_archetype},
+    },
+    mob_pool::MobPool,
+    node_refs::NodeRefs,
+    player::Player,
+    projectile_pool::ProjectilePool,
+    replay,
+    save::{NewRecords, SaveData},
+    spawn_director::SpawnDirector,
+    stats::RunStats,
+    ui::{SummaryKind, UserInterface},
+};
+#[cfg(feature = "dev-tools")]
+use crate::{console::DevConsole, debug_draw::DebugOverlay};
 use godot::{
     classes::{
-        AudioStreamPlayer, Button, ColorRect, Marker3D, MeshInstance3D, PathFollow3D, Timer,
-        object::ConnectFlags,
+        AudioStreamPlayer, Marker3D, Path3D, PathFollow3D, Timer, object::ConnectFlags,
+        timer::TimerProcessCallback,
     },
-    global::{randf, randf_range, randi},
+    global::{randf, randi},
     obj::{WithBaseField, WithUserSignals},
     prelude::*,
 };
+use std::f64::consts::PI;
+
+/// Id of the arena in save file.
+const ARENA_LEVEL_ID: &str = "arena";
+
+/// Maximum deviation of creep direction from the player, in radians.
+const SPAWN_ANGLE_JITTER: f64 = PI / 4.0;
+
+/// Distance between creeps spawned by splitter, in m.
+const SPLIT_SPREAD: f32 = 1.2;
+
+/// Chance to get improvement for squashed creep.
+const POWER_UP_CHANCE: f64 = 0.25;
 
 /// This enum store all improvements for player.
 /// He need for update UI.
+#[derive(GodotConvert, Var, Export, Default, Clone, Copy, PartialEq, Debug)]
+#[godot(via = i64)]
 pub enum Improvements {
     SlowCreeps,
     PlayerShield,
+    #[default]
     None,
 }
 
 /// This class store logic for Main scene.
 #[derive(GodotClass)]
 #[class(base = Node)]
-struct MainScene {
-    /// This field contain Mob scene for spawn creeps.
-    #[var]
-    pub mob_scene: OnReady<Gd<PackedScene>>,
-
+pub struct MainScene {
     /// Deceleration factor for creeps.
     slow_creeps: f64,
 
+    /// Active improvement.
+    power_up: Improvements,
+
+    /// Statistics of current run.
+    stats: RunStats,
+
+    /// Log of gameplay events.
+    events: EventLog,
+
+    /// Timer which disables active improvement, it is restarted by every new improvement.
+    improvement_timer: OnReady<Gd<Timer>>,
+
+    /// Game is played by the bot or tests, records, replays and
+    /// events are not saved. Must be set before the scene enters the tree.
+    pub automated: bool,
+
+    /// The player.
+    #[export]
+    player_path: NodePath,
+    player: OnReady<Gd<Player>>,
+
+    /// User interface.
+    #[export]
+    ui_path: NodePath,
+    ui: OnReady<Gd<UserInterface>>,
+
+    /// Camera follows the player.
+    #[export]
+    camera_pivot_path: NodePath,
+    camera_pivot: OnReady<Gd<Marker3D>>,
+
+    /// Pool of creeps.
+    #[export]
+    mob_pool_path: NodePath,
+    mob_pool: OnReady<Gd<MobPool>>,
+
+    /// Pool of projectiles.
+    #[export]
+    projectile_pool_path: NodePath,
+    projectile_pool: OnReady<Gd<ProjectilePool>>,
+
+    /// Director of waves.
+    #[export]
+    spawn_director_path: NodePath,
+    spawn_director: OnReady<Gd<SpawnDirector>>,
+
+    /// Path around the arena where creeps spawn.
+    #[export]
+    spawn_route_path: NodePath,
+    spawn_route: OnReady<Gd<Path3D>>,
+
+    /// Point of spawn on the path.
+    #[export]
+    spawn_location_path: NodePath,
+    spawn_location: OnReady<Gd<PathFollow3D>>,
+
+    /// Music of the game.
+    #[export]
+    background_music_path: NodePath,
+    background_music: OnReady<Gd<AudioStreamPlayer>>,
+
+    /// Adaptive difficulty controller, optional.
+    #[export]
+    difficulty_path: NodePath,
+    difficulty: Option<Gd<DifficultyController>>,
+
+    /// Procedural arena, optional.
+    #[export]
+    arena_path: NodePath,
+    arena: Option<Gd<ProceduralArena>>,
+
     base: Base<Node>,
 }
 
@@ -37,221 +140,528 @@ struct MainScene {
 impl INode for MainScene {
     fn init(base: Base<Node>) -> Self {
         Self {
-            mob_scene: OnReady::from_loaded("res://scenes/mob.tscn"),
             slow_creeps: 1.0,
+            power_up: Improvements::None,
+            stats: RunStats::default(),
+            events: EventLog::default(),
+            improvement_timer: OnReady::manual(),
+            automated: false,
+            player_path: "Player".into(),
+            player: OnReady::manual(),
+            ui_path: "UserInterface".into(),
+            ui: OnReady::manual(),
+            camera_pivot_path: "CameraPivot".into(),
+            camera_pivot: OnReady::manual(),
+            mob_pool_path: "MobPool".into(),
+            mob_pool: OnReady::manual(),
+            projectile_pool_path: "ProjectilePool".into(),
+            projectile_pool: OnReady::manual(),
+            spawn_director_path: "SpawnDirector".into(),
+            spawn_director: OnReady::manual(),
+            spawn_route_path: "SpawnPath".into(),
+            spawn_route: OnReady::manual(),
+            spawn_location_path: "SpawnPath/SpawnLocation".into(),
+            spawn_location: OnReady::manual(),
+            background_music_path: "BackgroundMusic".into(),
+            background_music: OnReady::manual(),
+            difficulty_path: "DifficultyController".into(),
+            difficulty: None,
+            arena_path: "ProceduralArena".into(),
+            arena: None,
             base
         }
     }
 
     fn ready(&mut self) {
-        // connect 'timeout' signal to spawn new mobs
-        self.base()
-            .get_node_as::<Timer>("MobTimer")
+        // automated runs are not logged
+        if self.automated {
+            self.events.disable();
+        }
+
+        // resolve nodes of the scene and check nodes of the player and the interface
+        // in the same pass, the scene is not started if any node is invalid
+        let mut refs = NodeRefs::new(self.to_gd().upcast());
+        if let Some(player) = refs.resolve(&mut self.player, &self.player_path) {
+            refs.include(player.bind().invalid_refs());
+        }
+        if let Some(ui) = refs.resolve(&mut self.ui, &self.ui_path) {
+            refs.include(ui.bind().invalid_refs());
+        }
+        refs.resolve(&mut self.camera_pivot, &self.camera_pivot_path);
+        refs.resolve(&mut self.mob_pool, &self.mob_pool_path);
+        refs.resolve(&mut self.projectile_pool, &self.projectile_pool_path);
+        refs.resolve(&mut self.spawn_director, &self.spawn_director_path);
+        refs.resolve(&mut self.spawn_route, &self.spawn_route_path);
+        refs.resolve(&mut self.spawn_location, &self.spawn_location_path);
+        refs.resolve(&mut self.background_music, &self.background_music_path);
+        self.difficulty = refs.resolve_optional(&self.difficulty_path);
+        self.arena = refs.resolve_optional(&self.arena_path);
+        if !refs.finish() {
+            return;
+        }
+
+        // create timer for disable improvements
+        let mut timer = Timer::new_alloc();
+        timer.set_one_shot(true);
+        timer.set_timer_process_callback(TimerProcessCallback::PHYSICS);
+        timer
             .signals()
             .timeout()
-            .connect_obj(self, Self::on_mob_timer_timeout);
+            .connect_obj(&self.to_gd(), Self::on_improvement_timer_timeout);
+        self.base_mut().add_child(&timer);
+        self.improvement_timer.init(timer);
+
+        // connect signals from spawn director to spawn new mobs and show waves
+        let mut director = self.spawn_director.clone();
 
-        // connect 'pressed' signal from StartButton to start new game
-        self.base()
-            .get_node_as::<Button>("UserInterface/MainHUD/StartButton")
+        director
             .signals()
-            .pressed()
-            .connect_obj(self, Self::on_start_button_pressed);
+            .spawn_requested()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_spawn_requested)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
 
-        // connect 'hit' signal from Player
-        self.base()
-            .get_node_as::<Player>("Player")
-            .bind_mut()
+        director
             .signals()
-            .hit()
-            .connect_obj(self, Self::on_player_hit);
+            .wave_started()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_wave_started)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        director
+            .signals()
+            .breather_started()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_breather_started)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
 
-        // show hud
-        self.base()
-            .get_node_as::<ColorRect>("UserInterface/MainHUD")
-            .show();
+        // subscribe to gameplay events, they are emitted inside physics of mobs and the player
+        let bus = EventBus::singleton();
+
+        bus.signals()
+            .mob_squashed()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_mob_squashed)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        // connect 'split_requested' signal from pool to spawn small creeps
+        self.mob_pool
+            .signals()
+            .split_requested()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_split_requested)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        // connect 'projectile_requested' signal from pool to fire projectiles
+        let projectile_pool = self.projectile_pool.clone();
+        self.mob_pool
+            .signals()
+            .projectile_requested()
+            .connect_obj(&projectile_pool, ProjectilePool::on_projectile_requested);
+
+        bus.signals()
+            .player_hit()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_player_hit)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        // connect signals from main menu and summary screen
+        let mut ui = self.ui.clone();
+
+        ui.signals()
+            .start_requested()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::new_game)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        ui.signals()
+            .retry_requested()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::new_game)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        ui.signals()
+            .menu_requested()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_menu_requested)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        // attach developer console and debug overlay in debug builds
+        #[cfg(feature = "dev-tools")]
+        self.attach_dev_tools();
+
+        // show main menu
+        set_game_state(GameState::Menu);
 
         // start music
-        self.base()
-            .get_node_as::<AudioStreamPlayer>("BackgroundMusic")
-            .play();
+        self.background_music.play();
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        // update statistics while player alive, in physics ticks for replays
+        let player = self.player.clone();
+        if player.bind().is_alive() {
+            self.stats.update(delta);
+
+            // difficulty of waves grows with score and time
+            let mut director = self.spawn_director.clone();
+            director
+                .bind_mut()
+                .set_progress(self.stats.score, self.stats.time_survived);
+
+            // spawn rate depends on performance of the player
+            if let Some(difficulty) = self.difficulty.clone() {
+                let factor = difficulty.bind().get_spawn_rate_factor();
+                director.bind_mut().set_spawn_rate_factor(factor);
+            }
+        }
     }
 
     fn process(&mut self, _delta: f64) {
         // get player position
-        let mut player_position = self.base().get_node_as::<Player>("Player").get_position();
+        let player = self.player.clone();
+        let mut player_position = player.get_position();
         player_position.y = 0.0;
 
         // move camera
-        self.base()
-            .get_node_as::<Marker3D>("CameraPivot")
-            .set_position(player_position);
+        self.camera_pivot.set_position(player_position);
     }
 }
 
 #[godot_api]
 impl MainScene {
-    /// Create mobs on timer timeout.
-    fn on_mob_timer_timeout(&mut self) {
-        // create a new mob
-        let mut mob = self.mob_scene.instantiate_as::<Mob>();
+    /// Create mob requested by spawn director.
+    fn on_spawn_requested(&mut self, archetype_id: GString, ratio: f32) {
+        let archetype = find_archetype(&archetype_id.to_string()).unwrap_or_else(default_archetype);
+
+        // set location on the SpawnLocation chosen by director
+        let mut mob_spawn_location = self.spawn_location.clone();
+        mob_spawn_location.set_progress_ratio(ratio);
+
+        self.spawn_mob(
+            archetype,
+            mob_spawn_location.get_global_position(),
+            SPAWN_ANGLE_JITTER,
+        );
+    }
 
-        // set slowdown
-        mob.bind_mut().slowdown = self.slow_creeps;
+    /// Show number of wave if wave started.
+    fn on_wave_started(&mut self, wave: i64) {
+        self.ui.bind_mut().set_wave(wave, false);
+    }
 
-        // chose a random location on the SpawnLocation
-        let mut mob_spawn_location = self
-            .base()
-            .get_node_as::<PathFollow3D>("SpawnPath/SpawnLocation");
-        mob_spawn_location.set_progress_ratio(randf() as f32);
+    /// Show number of next wave while breather.
+    fn on_breather_started(&mut self, next_wave: i64) {
+        self.ui.bind_mut().set_wave(next_wave, true);
+    }
+
+    /// Spawn creeps of splitter around his position.
+    fn on_split_requested(&mut self, position: Vector3, archetype_id: GString, count: i64) {
+        let archetype = find_archetype(&archetype_id.to_string()).unwrap_or_else(default_archetype);
+
+        for i in 0..count {
+            let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+            let offset = Vector3::FORWARD.rotated(Vector3::UP, angle) * SPLIT_SPREAD;
+            self.spawn_mob(archetype, position + offset, PI);
+        }
+    }
+
+    /// Get mob from pool and send him to the player.
+    fn spawn_mob(&mut self, archetype: &'static MobArchetype, position: Vector3, jitter: f64) {
+        // get mob from pool, skip spawn if too many mobs alive
+        let Some(mut mob) = self.mob_pool.bind_mut().acquire() else {
+            return;
+        };
+
+        // set slowdown and archetype, speed depends on performance of the player
+        let speed_factor = self
+            .difficulty
+            .as_ref()
+            .map_or(1.0, |difficulty| difficulty.bind().get_creep_speed_factor());
+        mob.bind_mut().slowdown = self.slow_creeps / speed_factor;
+        mob.bind_mut().set_archetype(archetype);
 
         // get player position
-        let player_position = self.base().get_node_as::<Player>("Player").get_position();
-
-        // initialize mob
-        // mob.bind_mut().initialize(
-        //     mob_spawn_location.get_position(),
-        //     player_position
-        // );
-
-        // we connect the mob to the score label to update the score
-        // upon squashing one
-        mob.signals()
-            .squashed()
-            .connect_builder()
-            .object(&self.to_gd())
-            .method_mut(Self::on_mob_squashed)
-            .flags(ConnectFlags::DEFERRED)
-            .done();
+        let player_position = self.player.get_position();
+
+        // initialize mob, he moves to the player
+        mob.set_position(position);
+        mob.bind_mut().initialize(MobMovement::AimAt {
+            target: player_position,
+            jitter,
+        });
 
-        // spawn the mob by adding it to the Main scene
-        self.base_mut().add_child(&mob);
+        // spawn the mob
+        mob.bind_mut().alive();
     }
 
     /// Update score if mob squashed and activate improvements if need.
-    fn on_mob_squashed(&mut self) {
+    fn on_mob_squashed(&mut self, score_value: i64) {
+        // update statistics, the creep is squashed under the player
+        self.stats.on_mob_squashed(score_value);
+        if let Some(mut difficulty) = self.difficulty.clone() {
+            difficulty.bind_mut().on_mob_squashed();
+        }
+        let position = self.player.get_global_position();
+        self.events.log(GameEvent::Squash {
+            position,
+            combo: self.stats.combo(),
+            score: score_value,
+        });
+
         // update score
-        self.base()
-            .get_node_as::<UserInterface>("UserInterface")
-            .bind_mut()
-            .on_mob_squashed();
+        EventBus::singleton()
+            .signals()
+            .score_changed()
+            .emit(self.stats.score);
+
+        // chance of improvement depends on performance of the player
+        let power_up_chance = POWER_UP_CHANCE
+            * self
+                .difficulty
+                .as_ref()
+                .map_or(1.0, |difficulty| difficulty.bind().get_power_up_factor());
 
         // add improvement if need
-        let shield_active = self
-            .base()
-            .get_node_as::<Player>("Player")
-            .bind()
-            .shield_active;
-
-        if self.slow_creeps == 1.0 && !shield_active {
-            if randf_range(-7.0, 20.0) >= 13.4 {
-                // active time of improvement is secs
-                let active_time: f64;
-
-                // 50% chance to get slowdown and 50% chance to get a shield
-                if randi() % 2 == 0 {
-                    // set improvement
-                    self.slow_creeps = 1.4;
-                    active_time = 20.0;
-
-                    // update UI
-                    self.base()
-                        .get_node_as::<UserInterface>("UserInterface")
-                        .bind_mut()
-                        .set_improvement(Improvements::SlowCreeps);
-                } else {
-                    // set visual improvement
-                    self.base()
-                        .get_node_as::<MeshInstance3D>("Player/Pivot/Shield")
-                        .show();
-
-                    // set improvement
-                    self.base()
-                        .get_node_as::<Player>("Player")
-                        .bind_mut()
-                        .shield_active = true;
-
-                    // set active time
-                    active_time = 10.0;
-
-                    // update UI
-                    self.base()
-                        .get_node_as::<UserInterface>("UserInterface")
-                        .bind_mut()
-                        .set_improvement(Improvements::PlayerShield);
-                }
-
-                // create timer for disable improvements
-                self.base()
-                    .get_tree()
-                    .unwrap()
-                    .create_timer(active_time)
-                    .unwrap()
-                    .signals()
-                    .timeout()
-                    .connect_obj(self, Self::on_improvement_timer_timeout);
-            }
+        if self.power_up == Improvements::None && randf() < power_up_chance {
+            // 50% chance to get slowdown and 50% chance to get a shield
+            let improvement = if randi() % 2 == 0 {
+                Improvements::SlowCreeps
+            } else {
+                Improvements::PlayerShield
+            };
+            self.activate_improvement(improvement);
         }
     }
 
+    /// Activate improvement and restart timer which disables it.
+    fn activate_improvement(&mut self, improvement: Improvements) {
+        // active time of improvement is secs
+        let active_time = match improvement {
+            Improvements::SlowCreeps => {
+                // set improvement
+                self.slow_creeps = 1.4;
+                self.events.log(GameEvent::PowerUpGained { kind: "slow" });
+                20.0
+            }
+            Improvements::PlayerShield => {
+                // the player activates shield himself
+                self.events.log(GameEvent::PowerUpGained { kind: "shield" });
+                10.0
+            }
+            Improvements::None => return,
+        };
+
+        // notify the player and UI
+        self.power_up = improvement;
+        EventBus::singleton()
+            .signals()
+            .power_up_changed()
+            .emit(improvement);
+
+        // restart timer for disable improvements, countdown of previous one is dropped
+        self.improvement_timer
+            .start_ex()
+            .time_sec(active_time)
+            .done();
+    }
+
     /// Disable all improvements on timeout.
     fn on_improvement_timer_timeout(&mut self) {
-        // deactivate all improvements
-        self.slow_creeps = 1.0;
-        self.base()
-            .get_node_as::<Player>("Player")
-            .bind_mut()
-            .shield_active = false;
+        // the timer is stopped if improvements are disabled by new game
+        self.improvement_timer.stop();
 
-        // hide player shield
-        self.base()
-            .get_node_as::<MeshInstance3D>("Player/Pivot/Shield")
-            .hide();
+        // log only active improvements
+        match self.power_up {
+            Improvements::SlowCreeps => {
+                self.events.log(GameEvent::PowerUpExpired { kind: "slow" });
+            }
+            Improvements::PlayerShield => {
+                self.events
+                    .log(GameEvent::PowerUpExpired { kind: "shield" });
+            }
+            Improvements::None => {}
+        }
 
-        // hide improvement on UI
-        self.base()
-            .get_node_as::<UserInterface>("UserInterface")
-            .bind_mut()
-            .set_improvement(Improvements::None);
+        // deactivate all improvements, notify the player and UI
+        self.slow_creeps = 1.0;
+        self.power_up = Improvements::None;
+        EventBus::singleton()
+            .signals()
+            .power_up_changed()
+            .emit(Improvements::None);
     }
 
-    /// Activate MainHUD if player die and deactivate all improvements.
+    /// Show summary screen if player die and save records.
     fn on_player_hit(&mut self) {
-        self.base()
-            .get_node_as::<ColorRect>("UserInterface/MainHUD")
-            .show();
-    }
+        // stop waves
+        self.spawn_director.bind_mut().stop();
+
+        // make the game easier after death
+        if let Some(mut difficulty) = self.difficulty.clone() {
+            difficulty.bind_mut().on_player_died();
+        }
 
-    /// Start game if 'StartButton' pressed.
-    fn on_start_button_pressed(&mut self) {
-        self.base()
-            .get_node_as::<AudioStreamPlayer>("UserInterface/ClickSound")
-            .play();
+        // update statistics
+        let mut player = self.player.clone();
+        let cause = player.bind().death_cause;
+        self.stats.death_cause = Some(cause);
+        self.events.log(GameEvent::Death {
+            position: player.get_global_position(),
+            cause,
+        });
+        self.events.log(GameEvent::RunEnded {
+            completed: false,
+            stats: self.stats.clone(),
+        });
+
+        // save replay and records of the run, automated runs don't replace them
+        let records = if self.automated {
+            NewRecords::default()
+        } else {
+            replay::finish_run(&mut player.bind_mut());
+            SaveData::load().submit_run(ARENA_LEVEL_ID, &self.stats, false)
+        };
+
+        // show summary
+        set_game_state(GameState::GameOver);
+        self.ui
+            .bind_mut()
+            .show_summary(SummaryKind::GameOver, &self.stats, records, false);
+    }
 
-        self.new_game();
+    /// Activate main menu if 'menu' selected on summary screen.
+    fn on_menu_requested(&mut self) {
+        set_game_state(GameState::Menu);
     }
 
     /// Start new game.
-    fn new_game(&mut self) {
-        // delete all mobs
-        self.base()
-            .get_tree()
-            .unwrap()
-            .call_group("mob", "queue_free", &[]);
+    pub fn new_game(&mut self) {
+        self.start_game(None);
+    }
+
+    /// Start new game, the run uses 'run_seed' or random seed if it is 'None'.
+    fn start_game(&mut self, run_seed: Option<u64>) {
+        // return all mobs to the pool
+        self.mob_pool.bind_mut().release_all();
+
+        // remove all projectiles
+        self.projectile_pool.bind_mut().release_all();
 
         // disable all improvements
         self.on_improvement_timer_timeout();
 
+        // reset statistics
+        self.stats = RunStats::default();
+        if let Some(mut difficulty) = self.difficulty.clone() {
+            difficulty.bind_mut().on_new_game();
+        }
+
+        // record or play input, random generator is seeded here
+        let seed = replay::start_run(&mut self.player.bind_mut(), ARENA_LEVEL_ID, run_seed);
+        self.events.log(GameEvent::RunStarted {
+            level_id: ARENA_LEVEL_ID.to_string(),
+            seed,
+        });
+
+        // generate new arena if need
+        self.rebuild_arena();
+
         // alive player
-        self.base()
-            .get_node_as::<Player>("Player")
-            .bind_mut()
-            .alive();
+        self.player.bind_mut().alive();
 
         // set UI to start new game
-        self.base()
-            .get_node_as::<UserInterface>("UserInterface")
-            .bind_mut()
-            .start_new_game();
+        set_game_state(GameState::Playing);
+
+        // start waves from the first one
+        self.spawn_director.bind_mut().start();
+    }
+
+    /// Generate new procedural arena and spawn path, if arena enabled.
+    fn rebuild_arena(&mut self) {
+        let Some(mut arena) = self.arena.clone() else {
+            return;
+        };
+
+        if !arena.bind().enabled {
+            return;
+        }
+
+        // generate arena around player spawn
+        let player = self.player.clone();
+        let spawn_points = arena.bind_mut().rebuild(&player.bind());
+
+        // update spawn path for creeps
+        self.spawn_route.set_curve(&closed_curve(&spawn_points));
+    }
+}
+
+/// Development tools of the arena.
+#[cfg(feature = "dev-tools")]
+impl MainScene {
+    /// Start new game with the seed, so the arena, waves and creeps repeat.
+    pub fn new_seeded_game(&mut self, run_seed: u64) {
+        self.start_game(Some(run_seed));
+    }
+
+    /// Statistics of current run.
+    pub fn stats(&self) -> &RunStats {
+        &self.stats
+    }
+
+    /// Return true if creeps are slowed by improvement.
+    pub fn is_slow_creeps(&self) -> bool {
+        self.slow_creeps != 1.0
+    }
+
+    /// Attach developer console and debug overlay,
+    /// commands of console which need the arena are sent by signals.
+    fn attach_dev_tools(&mut self) {
+        let console = DevConsole::new_alloc();
+        console
+            .signals()
+            .give_requested()
+            .connect_obj(&self.to_gd(), Self::on_give_requested);
+        console
+            .signals()
+            .spawn_requested()
+            .connect_obj(&self.to_gd(), Self::on_console_spawn_requested);
+        self.base_mut().add_child(&console);
+        self.base_mut().add_child(&DebugOverlay::new_alloc());
+    }
+
+    /// Activate improvement given by the developer console.
+    fn on_give_requested(&mut self, item: GString) {
+        let improvement = if item == "slow" {
+            Improvements::SlowCreeps
+        } else {
+            Improvements::PlayerShield
+        };
+        self.activate_improvement(improvement);
+    }
+
+    /// Spawn creeps requested by the developer console.
+    fn on_console_spawn_requested(&mut self, archetype_id: GString, count: i64) {
+        for _ in 0..count {
+            self.on_spawn_requested(archetype_id.clone(), randf() as f32);
+        }
     }
 }
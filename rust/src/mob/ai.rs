@@ -0,0 +1,233 @@
+//! This module store a state machine of mob AI. Mob patrols his path,
+//! notices the player, chases him, flees from the player with shield
+//! and returns to the path when the player is lost.
+//!
+//! State machine does not depend on Godot nodes, 'Mob' collect
+//! perception (vision cone and line of sight) and move by the state.
+
+/// This enum store all states of mob AI.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AiState {
+    /// Follow the path.
+    Patrol,
+
+    /// Stop and look at the player before chase.
+    Notice,
+
+    /// Move to the player.
+    Chase,
+
+    /// Move away from the player.
+    Flee,
+
+    /// Move back to the path.
+    ReturnToPath,
+}
+
+/// This struct store what the mob knows about the world in current frame.
+pub struct Perception {
+    /// Player is in vision cone and not hidden by walls.
+    pub sees_player: bool,
+
+    /// Player can squash the mob even from the side (has shield).
+    pub player_dangerous: bool,
+
+    /// Mob is near to his path point.
+    pub at_path: bool,
+}
+
+/// This struct store timings of transitions between states, in secs.
+pub struct AiTimings {
+    /// How long mob looks at the player before chase.
+    pub notice_time: f64,
+
+    /// How long mob continue chase or flee after lose the player.
+    pub lose_sight_time: f64,
+}
+
+/// This struct store current state of mob AI.
+pub struct MobAi {
+    /// Current state.
+    state: AiState,
+
+    /// How long current state is active, in secs.
+    state_time: f64,
+
+    /// How long the player is not seen, in secs.
+    unseen_time: f64,
+}
+
+impl Default for MobAi {
+    fn default() -> Self {
+        Self {
+            state: AiState::Patrol,
+            state_time: 0.0,
+            unseen_time: f64::INFINITY,
+        }
+    }
+}
+
+impl MobAi {
+    /// Current state.
+    pub fn state(&self) -> AiState {
+        self.state
+    }
+
+    /// Choose next state by perception and return it.
+    pub fn update(&mut self, delta: f64, perception: &Perception, timings: &AiTimings) -> AiState {
+        self.state_time += delta;
+        if perception.sees_player {
+            self.unseen_time = 0.0;
+        } else {
+            self.unseen_time += delta;
+        }
+        let lost = self.unseen_time >= timings.lose_sight_time;
+
+        // state if the player is just spotted
+        let spotted = if perception.player_dangerous {
+            AiState::Flee
+        } else {
+            AiState::Notice
+        };
+
+        let next = match self.state {
+            AiState::Patrol if perception.sees_player => spotted,
+            AiState::Patrol => AiState::Patrol,
+
+            AiState::Notice if perception.sees_player && perception.player_dangerous => {
+                AiState::Flee
+            }
+            AiState::Notice if lost => AiState::Patrol,
+            AiState::Notice if self.state_time >= timings.notice_time => AiState::Chase,
+            AiState::Notice => AiState::Notice,
+
+            AiState::Chase if perception.sees_player && perception.player_dangerous => {
+                AiState::Flee
+            }
+            AiState::Chase if lost => AiState::ReturnToPath,
+            AiState::Chase => AiState::Chase,
+
+            AiState::Flee if lost || !perception.player_dangerous => AiState::ReturnToPath,
+            AiState::Flee => AiState::Flee,
+
+            AiState::ReturnToPath if perception.sees_player => spotted,
+            AiState::ReturnToPath if perception.at_path => AiState::Patrol,
+            AiState::ReturnToPath => AiState::ReturnToPath,
+        };
+
+        if next != self.state {
+            self.state = next;
+            self.state_time = 0.0;
+        }
+        self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMINGS: AiTimings = AiTimings {
+        notice_time: 0.5,
+        lose_sight_time: 2.0,
+    };
+
+    fn perception(sees_player: bool, player_dangerous: bool, at_path: bool) -> Perception {
+        Perception {
+            sees_player,
+            player_dangerous,
+            at_path,
+        }
+    }
+
+    /// Mob which chases the player.
+    fn chasing_ai() -> MobAi {
+        let mut ai = MobAi::default();
+        let seen = perception(true, false, false);
+        ai.update(0.1, &seen, &TIMINGS);
+        ai.update(TIMINGS.notice_time, &seen, &TIMINGS);
+        assert_eq!(ai.state(), AiState::Chase);
+        ai
+    }
+
+    #[test]
+    fn patrol_until_player_seen() {
+        let mut ai = MobAi::default();
+        let unseen = perception(false, false, true);
+        assert_eq!(ai.update(10.0, &unseen, &TIMINGS), AiState::Patrol);
+    }
+
+    #[test]
+    fn notice_before_chase() {
+        let mut ai = MobAi::default();
+        let seen = perception(true, false, false);
+
+        assert_eq!(ai.update(0.1, &seen, &TIMINGS), AiState::Notice);
+        assert_eq!(ai.update(0.2, &seen, &TIMINGS), AiState::Notice);
+        assert_eq!(ai.update(0.3, &seen, &TIMINGS), AiState::Chase);
+    }
+
+    #[test]
+    fn notice_returns_to_patrol_if_player_lost() {
+        let mut ai = MobAi::default();
+        ai.update(0.1, &perception(true, false, false), &TIMINGS);
+
+        let unseen = perception(false, false, false);
+        assert_eq!(
+            ai.update(TIMINGS.lose_sight_time, &unseen, &TIMINGS),
+            AiState::Patrol
+        );
+    }
+
+    #[test]
+    fn chase_continues_until_lose_sight_timeout() {
+        let mut ai = chasing_ai();
+        let unseen = perception(false, false, false);
+
+        assert_eq!(ai.update(1.0, &unseen, &TIMINGS), AiState::Chase);
+        assert_eq!(ai.update(0.9, &unseen, &TIMINGS), AiState::Chase);
+        assert_eq!(ai.update(0.1, &unseen, &TIMINGS), AiState::ReturnToPath);
+    }
+
+    #[test]
+    fn chase_timeout_restarts_when_player_seen() {
+        let mut ai = chasing_ai();
+        let seen = perception(true, false, false);
+        let unseen = perception(false, false, false);
+
+        ai.update(1.5, &unseen, &TIMINGS);
+        ai.update(0.1, &seen, &TIMINGS);
+        assert_eq!(ai.update(1.5, &unseen, &TIMINGS), AiState::Chase);
+    }
+
+    #[test]
+    fn return_to_path_then_patrol() {
+        let mut ai = chasing_ai();
+        let away = perception(false, false, false);
+        ai.update(TIMINGS.lose_sight_time, &away, &TIMINGS);
+
+        assert_eq!(ai.update(0.1, &away, &TIMINGS), AiState::ReturnToPath);
+        let at_path = perception(false, false, true);
+        assert_eq!(ai.update(0.1, &at_path, &TIMINGS), AiState::Patrol);
+    }
+
+    #[test]
+    fn flee_from_dangerous_player() {
+        let mut ai = MobAi::default();
+        let dangerous = perception(true, true, false);
+        assert_eq!(ai.update(0.1, &dangerous, &TIMINGS), AiState::Flee);
+
+        let mut ai = chasing_ai();
+        assert_eq!(ai.update(0.1, &dangerous, &TIMINGS), AiState::Flee);
+    }
+
+    #[test]
+    fn flee_ends_when_player_not_dangerous() {
+        let mut ai = MobAi::default();
+        ai.update(0.1, &perception(true, true, false), &TIMINGS);
+
+        let safe = perception(true, false, false);
+        assert_eq!(ai.update(0.1, &safe, &TIMINGS), AiState::ReturnToPath);
+        assert_eq!(ai.update(0.1, &safe, &TIMINGS), AiState::Notice);
+    }
+}
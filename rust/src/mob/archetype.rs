@@ -0,0 +1,183 @@
+//! This module store archetypes of creeps. Archetype is a data, which
+//! describe look (tint, size), speed, health, score and behaviour of creep.
+//! New creep types are added to 'ARCHETYPES' without change of 'Mob' code.
+
+use crate::projectile::ProjectileSettings;
+use godot::{builtin::Color, global::randf_range};
+
+/// This enum store special behaviours of creeps.
+#[derive(Clone, Copy)]
+pub enum MobBehaviour {
+    /// Move as selected by 'MobMovement', without special behaviour.
+    Walker,
+
+    /// Turn to the player while moving, turn speed in rad/s.
+    Chaser { turn_speed: f32 },
+
+    /// Hop periodically, interval in secs and hop height in m.
+    Jumper { interval: f64, height: f32 },
+
+    /// Spawn other creeps when squashed.
+    Splitter { child: &'static str, count: i64 },
+
+    /// Fire at the player in range (m) every 'interval' secs,
+    /// mob glows 'windup' secs before the shot.
+    Shooter {
+        interval: f64,
+        windup: f64,
+        range: f32,
+        projectile: ProjectileSettings,
+    },
+}
+
+/// This struct describe one type of creep.
+pub struct MobArchetype {
+    /// Id of archetype, used in scenes and level files.
+    pub id: &'static str,
+
+    /// Color mixed with mesh of the creep, 'None' keeps original look.
+    pub tint: Option<Color>,
+
+    /// How much score the player gets for squash.
+    pub score_value: i64,
+
+    /// Relative chance to spawn this creep in arena, 0 means never.
+    pub spawn_weight: f64,
+
+    /// How much stomps needed to squash the creep.
+    pub health: i64,
+
+    /// Speed multiplier.
+    pub speed_factor: f32,
+
+    /// Scale multiplier.
+    pub scale_factor: f32,
+
+    /// Special behaviour.
+    pub behaviour: MobBehaviour,
+}
+
+impl MobArchetype {
+    /// Return true if creep spawns other creeps when squashed,
+    /// only the arena spawns them, levels don't support splitters.
+    pub fn is_splitter(&self) -> bool {
+        matches!(self.behaviour, MobBehaviour::Splitter { .. })
+    }
+}
+
+/// All archetypes of creeps, first one is the default.
+pub static ARCHETYPES: [MobArchetype; 7] = [
+    MobArchetype {
+        id: "normal",
+        tint: None,
+        score_value: 1,
+        spawn_weight: 6.0,
+        health: 1,
+        speed_factor: 1.0,
+        scale_factor: 1.0,
+        behaviour: MobBehaviour::Walker,
+    },
+    MobArchetype {
+        id: "chaser",
+        tint: Some(Color::from_rgb(0.9, 0.2, 0.2)),
+        score_value: 2,
+        spawn_weight: 2.0,
+        health: 1,
+        speed_factor: 0.8,
+        scale_factor: 1.0,
+        behaviour: MobBehaviour::Chaser { turn_speed: 1.2 },
+    },
+    MobArchetype {
+        id: "jumper",
+        tint: Some(Color::from_rgb(0.3, 0.9, 0.3)),
+        score_value: 2,
+        spawn_weight: 2.0,
+        health: 1,
+        speed_factor: 1.0,
+        scale_factor: 0.9,
+        behaviour: MobBehaviour::Jumper {
+            interval: 1.6,
+            height: 1.8,
+        },
+    },
+    MobArchetype {
+        id: "armored",
+        tint: Some(Color::from_rgb(0.55, 0.55, 0.6)),
+        score_value: 3,
+        spawn_weight: 1.0,
+        health: 2,
+        speed_factor: 0.7,
+        scale_factor: 1.2,
+        behaviour: MobBehaviour::Walker,
+    },
+    MobArchetype {
+        id: "splitter",
+        tint: Some(Color::from_rgb(0.7, 0.3, 0.9)),
+        score_value: 2,
+        spawn_weight: 1.0,
+        health: 1,
+        speed_factor: 0.9,
+        scale_factor: 1.1,
+        behaviour: MobBehaviour::Splitter {
+            child: "small",
+            count: 2,
+        },
+    },
+    MobArchetype {
+        id: "small",
+        tint: Some(Color::from_rgb(1.0, 0.8, 0.2)),
+        score_value: 1,
+        spawn_weight: 2.0,
+        health: 1,
+        speed_factor: 1.6,
+        scale_factor: 0.6,
+        behaviour: MobBehaviour::Walker,
+    },
+    MobArchetype {
+        id: "shooter",
+        tint: Some(Color::from_rgb(0.2, 0.6, 1.0)),
+        score_value: 3,
+        spawn_weight: 1.0,
+        health: 1,
+        speed_factor: 0.6,
+        scale_factor: 1.0,
+        behaviour: MobBehaviour::Shooter {
+            interval: 2.5,
+            windup: 0.6,
+            range: 14.0,
+            projectile: ProjectileSettings {
+                speed: 9.0,
+                lifetime: 3.0,
+                gravity: 0.0,
+            },
+        },
+    },
+];
+
+/// Default archetype.
+pub fn default_archetype() -> &'static MobArchetype {
+    &ARCHETYPES[0]
+}
+
+/// Find archetype by id.
+pub fn find_archetype(id: &str) -> Option<&'static MobArchetype> {
+    ARCHETYPES.iter().find(|archetype| archetype.id == id)
+}
+
+/// Choose random archetype, chance of every archetype depends on spawn weight.
+pub fn random_archetype() -> &'static MobArchetype {
+    let total_weight: f64 = ARCHETYPES
+        .iter()
+        .map(|archetype| archetype.spawn_weight)
+        .sum();
+    let mut value = randf_range(0.0, total_weight);
+
+    for archetype in &ARCHETYPES {
+        if value < archetype.spawn_weight {
+            return archetype;
+        }
+        value -= archetype.spawn_weight;
+    }
+
+    default_archetype()
+}
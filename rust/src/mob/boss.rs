@@ -0,0 +1,421 @@
+//! This module store a boss of the level. Boss is built on 'Mob': he
+//! creates a mob body with boss archetype and controls it. Boss has a
+//! health pool which needs several stomps, and phases with different
+//! attacks (charge, summon minions, shockwave jump).
+
+use super::{
+    HOP_DURATION, Mob, MobMovement, WORLD_COLLISION_LAYER,
+    archetype::{MobArchetype, MobBehaviour},
+};
+use crate::player::{DeathCause, Player};
+use godot::{
+    classes::{INode3D, object::ConnectFlags},
+    obj::WithBaseField,
+    prelude::*,
+};
+
+/// Archetype of the boss body, it is not spawned in arena.
+static BOSS_ARCHETYPE: MobArchetype = MobArchetype {
+    id: "boss",
+    tint: Some(Color::from_rgb(0.8, 0.1, 0.1)),
+    score_value: 10,
+    spawn_weight: 0.0,
+    health: 9,
+    speed_factor: 1.0,
+    scale_factor: 2.5,
+    behaviour: MobBehaviour::Walker,
+};
+
+/// This enum store all attacks of the boss.
+#[derive(Clone, Copy)]
+pub enum BossAttack {
+    /// Run to the player with speed (m/s) for duration (secs).
+    Charge { speed: f32, duration: f64 },
+
+    /// Summon minions of archetype from spawners of the level.
+    Summon { archetype: &'static str, count: i64 },
+
+    /// Jump and kill the player on the ground in radius (m) after landing.
+    Shockwave { height: f32, radius: f32 },
+}
+
+/// This struct describe one phase of the boss.
+pub struct BossPhase {
+    /// Phase starts when part of health left is not more than this value.
+    pub health_ratio: f32,
+
+    /// Color of the boss in this phase.
+    pub tint: Color,
+
+    /// Speed of walk to the player between attacks, in m/s.
+    pub walk_speed: f32,
+
+    /// Time between attacks, in secs.
+    pub cooldown: f64,
+
+    /// Attacks of the phase, they are used in order.
+    pub attacks: &'static [BossAttack],
+}
+
+/// All phases of the boss, from full health to the last stomp.
+static BOSS_PHASES: [BossPhase; 3] = [
+    BossPhase {
+        health_ratio: 1.0,
+        tint: Color::from_rgb(0.8, 0.1, 0.1),
+        walk_speed: 2.0,
+        cooldown: 2.5,
+        attacks: &[BossAttack::Charge {
+            speed: 12.0,
+            duration: 1.0,
+        }],
+    },
+    BossPhase {
+        health_ratio: 0.67,
+        tint: Color::from_rgb(0.9, 0.45, 0.1),
+        walk_speed: 2.5,
+        cooldown: 2.0,
+        attacks: &[
+            BossAttack::Summon {
+                archetype: "small",
+                count: 3,
+            },
+            BossAttack::Charge {
+                speed: 14.0,
+                duration: 1.0,
+            },
+        ],
+    },
+    BossPhase {
+        health_ratio: 0.34,
+        tint: Color::from_rgb(0.6, 0.1, 0.8),
+        walk_speed: 3.0,
+        cooldown: 1.5,
+        attacks: &[
+            BossAttack::Shockwave {
+                height: 3.0,
+                radius: 6.0,
+            },
+            BossAttack::Charge {
+                speed: 16.0,
+                duration: 0.8,
+            },
+            BossAttack::Summon {
+                archetype: "chaser",
+                count: 2,
+            },
+        ],
+    },
+];
+
+/// Find phase for part of health left.
+fn phase_for(health_ratio: f32) -> usize {
+    BOSS_PHASES
+        .iter()
+        .rposition(|phase| phase.health_ratio >= health_ratio)
+        .unwrap_or(0)
+}
+
+/// This enum store what the boss does now.
+#[derive(Clone, Copy)]
+enum BossAction {
+    /// Walk to the player until next attack.
+    Walk { time_left: f64 },
+
+    /// Stand and look at the player before attack.
+    Windup { attack: BossAttack, time_left: f64 },
+
+    /// Run in direction.
+    Charge {
+        direction: Vector3,
+        speed: f32,
+        time_left: f64,
+    },
+
+    /// Jump before shockwave.
+    Jump { height: f32, radius: f32, time: f64 },
+
+    /// Boss is defeated.
+    Defeated,
+}
+
+/// This class is a boss, he controls the mob body created on ready.
+#[derive(GodotClass)]
+#[class(base = Node3D)]
+pub struct Boss {
+    /// Time of attack preparation, the player can see what the boss will do, in secs.
+    #[export]
+    windup_time: f64,
+
+    /// Duration of shockwave jump, in secs.
+    #[export]
+    jump_duration: f64,
+
+    /// Body of the boss.
+    mob: Option<Gd<Mob>>,
+
+    /// Current phase.
+    phase: usize,
+
+    /// Index of next attack in the phase.
+    next_attack: usize,
+
+    /// What the boss does now.
+    action: BossAction,
+
+    /// Current height of jump, in m.
+    jump_offset: f32,
+
+    base: Base<Node3D>,
+}
+
+#[godot_api]
+impl INode3D for Boss {
+    fn init(base: Base<Node3D>) -> Self {
+        Self {
+            windup_time: 0.7,
+            jump_duration: HOP_DURATION * 2.0,
+            mob: None,
+            phase: 0,
+            next_attack: 0,
+            action: BossAction::Walk { time_left: 0.0 },
+            jump_offset: 0.0,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        // create body of the boss
+        let mut mob = load::<PackedScene>("res://scenes/objecst/mob.tscn").instantiate_as::<Mob>();
+        mob.set_name("Body");
+        mob.bind_mut().min_scale = 1.0;
+        mob.bind_mut().max_scale = 1.0;
+        self.base_mut().add_child(&mob);
+
+        // boss must not walk through walls
+        mob.set_collision_mask(WORLD_COLLISION_LAYER);
+
+        // connect signals, body is still bound while he emits them
+        mob.signals()
+            .damaged()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_damaged)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        mob.signals()
+            .squashed()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_squashed)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        {
+            let mut mob = mob.bind_mut();
+            mob.set_archetype(&BOSS_ARCHETYPE);
+            mob.initialize(MobMovement::Idle);
+        }
+        self.mob = Some(mob);
+
+        self.reset();
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        let Some(mut mob) = self.mob.clone() else {
+            return;
+        };
+        let player = mob.bind().find_player();
+
+        match self.action {
+            BossAction::Walk { time_left } => {
+                let time_left = time_left - delta;
+                let phase = &BOSS_PHASES[self.phase];
+
+                // walk to the player
+                let direction = player.as_ref().map_or(Vector3::ZERO, |player| {
+                    mob.bind().direction_to(player.get_global_position())
+                });
+                mob.bind_mut()
+                    .move_in_direction(direction, phase.walk_speed);
+
+                // prepare next attack
+                self.action = if time_left <= 0.0 {
+                    let attack = phase.attacks[self.next_attack % phase.attacks.len()];
+                    self.next_attack += 1;
+
+                    BossAction::Windup {
+                        attack,
+                        time_left: self.windup_time,
+                    }
+                } else {
+                    BossAction::Walk { time_left }
+                };
+            }
+            BossAction::Windup { attack, time_left } => {
+                let time_left = time_left - delta;
+
+                // stand and look at the player
+                let direction = player.as_ref().map_or(Vector3::ZERO, |player| {
+                    mob.bind().direction_to(player.get_global_position())
+                });
+                mob.bind_mut().move_in_direction(direction, 0.0);
+
+                self.action = if time_left <= 0.0 {
+                    self.start_attack(attack, direction)
+                } else {
+                    BossAction::Windup { attack, time_left }
+                };
+            }
+            BossAction::Charge {
+                direction,
+                speed,
+                time_left,
+            } => {
+                let time_left = time_left - delta;
+                mob.bind_mut().move_in_direction(direction, speed);
+
+                // charge stops after time or on a wall
+                self.action = if time_left <= 0.0 || mob.is_on_wall() {
+                    self.walk()
+                } else {
+                    BossAction::Charge {
+                        direction,
+                        speed,
+                        time_left,
+                    }
+                };
+            }
+            BossAction::Jump {
+                height,
+                radius,
+                time,
+            } => {
+                let time = time + delta;
+                mob.set_velocity(Vector3::ZERO);
+
+                // jump is a parabola from 0 to 'height' and back
+                let phase = (time / self.jump_duration).min(1.0);
+                let offset = 4.0 * height * (phase * (1.0 - phase)) as f32;
+                let mut position = mob.get_position();
+                position.y += offset - self.jump_offset;
+                mob.set_position(position);
+                self.jump_offset = offset;
+
+                self.action = if phase >= 1.0 {
+                    self.shockwave(radius, player);
+                    self.walk()
+                } else {
+                    BossAction::Jump {
+                        height,
+                        radius,
+                        time,
+                    }
+                };
+            }
+            BossAction::Defeated => {}
+        }
+    }
+}
+
+#[godot_api]
+impl Boss {
+    /// Emitted if health of the boss changed.
+    #[signal]
+    pub fn health_changed(health: i64, max_health: i64);
+
+    /// Emitted if the boss wants minions near spawners of the level.
+    #[signal]
+    pub fn summon_requested(archetype_id: GString, count: i64);
+
+    /// Emitted if the boss squashed.
+    #[signal]
+    pub fn defeated(score_value: i64);
+
+    /// Restore the boss to the first phase with full health.
+    pub fn reset(&mut self) {
+        self.phase = 0;
+        self.next_attack = 0;
+        self.jump_offset = 0.0;
+        self.action = self.walk();
+
+        let Some(mut mob) = self.mob.clone() else {
+            return;
+        };
+        mob.bind_mut().alive();
+        mob.bind_mut().apply_tint(Some(BOSS_PHASES[0].tint));
+
+        let health = BOSS_ARCHETYPE.health;
+        self.signals().health_changed().emit(health, health);
+    }
+
+    /// Start walk before next attack.
+    fn walk(&self) -> BossAction {
+        BossAction::Walk {
+            time_left: BOSS_PHASES[self.phase].cooldown,
+        }
+    }
+
+    /// Start the attack in direction of the player.
+    fn start_attack(&mut self, attack: BossAttack, direction: Vector3) -> BossAction {
+        match attack {
+            BossAttack::Charge { speed, duration } => BossAction::Charge {
+                direction,
+                speed,
+                time_left: duration,
+            },
+            BossAttack::Summon { archetype, count } => {
+                self.signals()
+                    .summon_requested()
+                    .emit(&GString::from(archetype), count);
+                self.walk()
+            }
+            BossAttack::Shockwave { height, radius } => BossAction::Jump {
+                height,
+                radius,
+                time: 0.0,
+            },
+        }
+    }
+
+    /// Kill the player on the ground near landed boss, jump saves the player.
+    fn shockwave(&mut self, radius: f32, player: Option<Gd<Player>>) {
+        let (Some(mob), Some(mut player)) = (self.mob.as_ref(), player) else {
+            return;
+        };
+
+        let to_player = player.get_global_position() - mob.get_global_position();
+        let distance = Vector2::new(to_player.x, to_player.z).length();
+        if distance < radius && player.is_on_floor() {
+            player.bind_mut().kill(DeathCause::Creep);
+        }
+    }
+
+    /// Change phase and update health bar if the boss lose health.
+    fn on_damaged(&mut self, health: i64) {
+        let Some(mut mob) = self.mob.clone() else {
+            return;
+        };
+
+        // next phase starts from first attack
+        let max_health = BOSS_ARCHETYPE.health;
+        let phase = phase_for(health as f32 / max_health as f32);
+        if phase != self.phase {
+            self.phase = phase;
+            self.next_attack = 0;
+        }
+        mob.bind_mut()
+            .apply_tint(Some(BOSS_PHASES[self.phase].tint));
+
+        self.signals().health_changed().emit(health, max_health);
+    }
+
+    /// Stop the boss if he squashed.
+    fn on_squashed(&mut self, score_value: i64) {
+        self.action = BossAction::Defeated;
+
+        let max_health = BOSS_ARCHETYPE.health;
+        self.signals().health_changed().emit(0, max_health);
+        self.signals().defeated().emit(score_value);
+    }
+}
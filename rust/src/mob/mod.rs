@@ -0,0 +1,892 @@
+//! This file contain the Mob class for Godot.
+//! Mob is a enemy for player and mob can kill the player.
+
+mod ai;
+pub mod archetype;
+pub mod boss;
+pub mod path;
+
+use crate::{
+    event_bus::EventBus,
+    player::{PLAYER_GROUP, Player},
+    projectile::ProjectileSettings,
+};
+use ai::{AiState, AiTimings, MobAi, Perception};
+use archetype::{MobArchetype, MobBehaviour, default_archetype, find_archetype};
+use godot::{
+    classes::{
+        AnimationPlayer, AudioStreamPlayer, CharacterBody3D, CollisionShape3D, GpuParticles3D,
+        ICharacterBody3D, Material, MeshInstance3D, NavigationAgent3D, Path3D, PathFollow3D,
+        PhysicsRayQueryParameters3D, StandardMaterial3D, VisibleOnScreenNotifier3D,
+        base_material_3d::Transparency, object::ConnectFlags,
+    },
+    global::{randf_range, randi_range, wrapf},
+    obj::WithBaseField,
+    prelude::*,
+};
+use path::{PathMode, PathStep};
+use std::f64::consts::PI;
+
+/// Duration of one hop of jumper, in secs.
+const HOP_DURATION: f64 = 0.6;
+
+/// Opacity of archetype tint over the mesh.
+const TINT_ALPHA: f32 = 0.55;
+
+/// Color of shooter before the shot.
+const TELEGRAPH_COLOR: Color = Color::from_rgb(1.0, 1.0, 1.0);
+
+/// Collision layer of the world ("word" in project settings).
+pub const WORLD_COLLISION_LAYER: u32 = 4;
+
+/// Group of all mobs.
+pub const MOB_GROUP: &str = "mob";
+
+/// Height of mob and player eyes for line of sight, in m.
+const EYE_HEIGHT: f32 = 0.5;
+
+/// Distance to the path point when returned mob continue patrol, in m.
+const PATH_REACH_DISTANCE: f32 = 0.5;
+
+/// How often navigating mob recalculate his path, in secs.
+const REPATH_INTERVAL: f64 = 0.5;
+
+/// How far the target must move to recalculate path immediately, in m.
+const REPATH_DISTANCE: f32 = 1.0;
+
+/// Minimal random speed on the path, in m/s.
+const MIN_PATH_SPEED: f64 = 6.0;
+
+/// Maximal random speed on the path, in m/s.
+const MAX_PATH_SPEED: f64 = 20.0;
+
+/// This enum store how the mob moves, movement is selected at spawn.
+pub enum MobMovement {
+    /// Move in a straight line to the target, direction is rotated
+    /// by random angle from '-jitter' to 'jitter' (in radians).
+    AimAt { target: Vector3, jitter: f64 },
+
+    /// Move along the path with 'path_speed' and 'path_mode' of the mob.
+    FollowPath {
+        follow_path: Gd<PathFollow3D>,
+        path: Gd<Path3D>,
+    },
+
+    /// Move to the player by navigation mesh, mob must use navigation.
+    Navigate,
+
+    /// Stay on the place.
+    Idle,
+}
+
+impl MobMovement {
+    /// Follow the path if both nodes exist, otherwise stay on the place.
+    pub fn from_path(follow_path: Option<Gd<PathFollow3D>>, path: Option<Gd<Path3D>>) -> Self {
+        match (follow_path, path) {
+            (Some(follow_path), Some(path)) => Self::FollowPath { follow_path, path },
+            _ => Self::Idle,
+        }
+    }
+}
+
+/// This class is a enemy for player.
+#[derive(GodotClass)]
+#[class(base = CharacterBody3D)]
+pub struct Mob {
+    /// Improvement for player.
+    pub slowdown: f64,
+
+    /// Set 'self.position' to spawn_coords if mob alive.
+    pub spawn_coords: Vector3,
+
+    /// Set 'self.velocity' to spawn_velocity if mob alive.
+    pub spawn_velocity: Vector3,
+
+    /// Minimum speed of the mob, in m/s.
+    #[export]
+    pub min_speed: i64,
+
+    /// Maximum speed of the mob, in m/s.
+    #[export]
+    pub max_speed: i64,
+
+    /// Minimum scale of the mob.
+    #[export]
+    pub min_scale: f64,
+
+    /// Maximum scale of the mob.
+    #[export]
+    pub max_scale: f64,
+
+    /// Id of archetype (see 'ARCHETYPES'), used for mobs placed in scenes.
+    #[export]
+    pub archetype_id: GString,
+
+    /// Speed on the path, in m/s, 0 means random speed.
+    #[export]
+    pub path_speed: f32,
+
+    /// What the mob does at the end of the path.
+    #[export]
+    pub path_mode: PathMode,
+
+    /// Enable AI (patrol, chase and flee), works only for mobs on path.
+    #[export]
+    pub ai_enabled: bool,
+
+    /// How far the mob sees the player, in m.
+    #[export]
+    pub vision_radius: f32,
+
+    /// Angle of vision cone, in degrees.
+    #[export]
+    pub vision_angle: f32,
+
+    /// How long the mob looks at the player before chase, in secs.
+    #[export]
+    pub notice_time: f64,
+
+    /// How long the mob chases or flees after lose the player, in secs.
+    #[export]
+    pub lose_sight_time: f64,
+
+    /// Speed of chase and flee, in m/s.
+    #[export]
+    pub chase_speed: f32,
+
+    /// Speed of return to the path, in m/s.
+    #[export]
+    pub return_speed: f32,
+
+    /// Move by navigation mesh ('NavigationRegion3D' of level) instead of straight line.
+    /// Mob without path chases the player, mob with AI uses navigation for chase and return.
+    #[export]
+    pub use_navigation: bool,
+
+    /// Radius of the mob for avoidance of other mobs, in m.
+    #[export]
+    pub avoidance_radius: f32,
+
+    /// How the mob moves.
+    movement: MobMovement,
+
+    /// Speed on the path selected on initialize, in m/s.
+    current_path_speed: f32,
+
+    /// Direction of move on the path: 1 forward, -1 backward, 0 stopped.
+    path_direction: f32,
+
+    /// State of AI.
+    ai: MobAi,
+
+    /// Navigation agent, if mob uses navigation.
+    agent: Option<Gd<NavigationAgent3D>>,
+
+    /// Time from last path calculation, in secs.
+    repath_time: f64,
+
+    /// Archetype of the mob.
+    archetype: &'static MobArchetype,
+
+    /// How much stomps left to squash the mob.
+    health: i64,
+
+    /// Time from last hop, in secs (only for jumpers).
+    hop_time: f64,
+
+    /// Current height of hop, in m (only for jumpers).
+    hop_offset: f32,
+
+    /// Time from last shot, in secs (only for shooters).
+    shoot_time: f64,
+
+    base: Base<CharacterBody3D>,
+}
+
+#[godot_api]
+impl ICharacterBody3D for Mob {
+    fn init(base: Base<CharacterBody3D>) -> Self {
+        Self {
+            slowdown: 1.0,
+            spawn_coords: Vector3::ZERO,
+            spawn_velocity: Vector3::ZERO,
+            min_speed: 10,
+            max_speed: 18,
+            min_scale: 0.84,
+            max_scale: 1.09,
+            archetype_id: default_archetype().id.into(),
+            path_speed: 0.0,
+            path_mode: PathMode::Loop,
+            ai_enabled: false,
+            vision_radius: 8.0,
+            vision_angle: 120.0,
+            notice_time: 0.6,
+            lose_sight_time: 2.0,
+            chase_speed: 5.0,
+            return_speed: 3.0,
+            use_navigation: false,
+            avoidance_radius: 0.8,
+            movement: MobMovement::Idle,
+            current_path_speed: 0.0,
+            path_direction: 1.0,
+            ai: MobAi::default(),
+            agent: None,
+            repath_time: 0.0,
+            archetype: default_archetype(),
+            health: default_archetype().health,
+            hop_time: 0.0,
+            hop_offset: 0.0,
+            shoot_time: 0.0,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        // connect method for screen_exited signal
+        self.base()
+            .get_node_as::<VisibleOnScreenNotifier3D>("VisibleNotifier")
+            .signals()
+            .screen_exited()
+            .connect_obj(self, Self::on_visible_on_screen_notifier_3d_screen_exited);
+
+        // connect method for DeadSound.finished signal
+        self.base()
+            .get_node_as::<GpuParticles3D>("DeadEffect")
+            .signals()
+            .finished()
+            .connect_obj(self, Self::on_dead_effect_finished);
+
+        // set archetype from scene
+        let archetype = find_archetype(&self.archetype_id.to_string()).unwrap_or_else(|| {
+            godot_warn!("unknown mob archetype '{}'", self.archetype_id);
+            default_archetype()
+        });
+        self.set_archetype(archetype);
+
+        // mobs with AI or navigation leave the path, so they must not walk through walls
+        if self.ai_enabled || self.use_navigation {
+            self.base_mut().set_collision_mask(WORLD_COLLISION_LAYER);
+        }
+
+        // create navigation agent
+        if self.use_navigation {
+            self.create_agent();
+        }
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        // squashed mob does not move
+        if self.health == 0 {
+            return;
+        }
+
+        // chasers turn to the player
+        if let MobBehaviour::Chaser { turn_speed } = self.archetype.behaviour {
+            self.turn_to_player(turn_speed as f64 * delta);
+        }
+
+        // AI moves the mob if he is not patrolling,
+        // otherwise moves the mob along the trajectory if possible
+        let ai_state = self.update_ai(delta);
+        if ai_state != AiState::Patrol {
+            self.move_by_ai(ai_state, delta);
+        } else if let MobMovement::Navigate = self.movement {
+            if let Some(player) = self.find_player() {
+                let direction = self.route_to(player.get_global_position(), delta);
+                self.move_in_direction(direction, self.chase_speed);
+            }
+        } else if let MobMovement::FollowPath { follow_path, path } = &self.movement {
+            let mut follow_path = follow_path.clone();
+            let path = path.clone();
+
+            // get oldest position
+            let old_position = self.base().get_position();
+
+            // calculate new position (hop is added on top of the path)
+            let mut new_position = follow_path.get_position() + path.get_position();
+            new_position.y += self.hop_offset;
+
+            // calculate rotation, stopped mob keeps his rotation
+            if old_position.distance_to(new_position) > 0.001 {
+                self.base_mut()
+                    .look_at_from_position(old_position, new_position);
+            }
+
+            // set new position
+            self.base_mut().set_position(new_position);
+            self.base_mut().set_velocity(Vector3::ZERO);
+
+            // update progress, speed is in m/s and depends on slowdown
+            let length = path
+                .get_curve()
+                .map_or(0.0, |curve| curve.get_baked_length());
+            let distance = self.current_path_speed / self.slowdown as f32 * delta as f32;
+            let step = path::advance(
+                PathStep {
+                    progress: follow_path.get_progress(),
+                    direction: self.path_direction,
+                },
+                distance,
+                length,
+                self.path_mode,
+            );
+
+            follow_path.set_progress(step.progress);
+            self.path_direction = step.direction;
+        }
+
+        self.base_mut().move_and_slide();
+
+        // jumpers hop periodically
+        if let MobBehaviour::Jumper { interval, height } = self.archetype.behaviour {
+            self.update_hop(delta, interval, height);
+        }
+
+        // shooters fire at the player periodically
+        if let MobBehaviour::Shooter {
+            interval,
+            windup,
+            range,
+            projectile,
+        } = self.archetype.behaviour
+        {
+            self.update_shooter(delta, interval, windup, range, projectile);
+        }
+    }
+}
+
+#[godot_api]
+impl Mob {
+    #[signal]
+    pub fn squashed(score_value: i64);
+
+    /// Emitted if mob lost health from stomp, but he is not squashed yet.
+    #[signal]
+    pub fn damaged(health: i64);
+
+    /// Emitted if splitter squashed, owner of the mob must spawn new mobs.
+    #[signal]
+    pub fn split_requested(position: Vector3, archetype_id: GString, count: i64);
+
+    /// Emitted if shooter fires, owner of the mob must create a projectile.
+    #[signal]
+    pub fn projectile_requested(
+        position: Vector3,
+        direction: Vector3,
+        speed: f32,
+        lifetime: f64,
+        gravity: f32,
+    );
+
+    /// Emitted if mob is not needed anymore (left the screen or dead effect finished).
+    /// Owner of the mob decide to delete him or return to the pool.
+    #[signal]
+    pub fn released(mob: Gd<Mob>);
+
+    /// This function will be called from BaseLevel or MainScene and need for init mob.
+    /// Mob must be placed on spawn position before call.
+    pub fn initialize(&mut self, movement: MobMovement) {
+        // set spawn coords
+        let position = self.base().get_position();
+        self.spawn_coords = position;
+
+        // we calculate a random speed
+        let random_speed = randi_range(self.min_speed, self.max_speed) as f32
+            * self.archetype.speed_factor
+            / self.slowdown as f32;
+
+        // turn the mob to the target and calculate velocity
+        if let MobMovement::AimAt { target, jitter } = movement {
+            // target on the same height, so mob moves horizontally
+            let target = Vector3::new(target.x, position.y, target.z);
+            if position.distance_to(target) > 0.01 {
+                self.base_mut().look_at_from_position(position, target);
+            }
+
+            // rotate mob randomly, so he does not move exactly toward the target
+            self.base_mut()
+                .rotate_y(randf_range(-jitter, jitter) as f32);
+
+            // calculate velocity in direction of the mob
+            let rotation = self.base().get_rotation().y;
+            self.spawn_velocity = (Vector3::FORWARD * random_speed).rotated(Vector3::UP, rotation);
+        } else {
+            self.spawn_velocity = Vector3::ZERO;
+        }
+
+        // select speed on the path, progress is wrapped by 'path::advance'
+        if let MobMovement::FollowPath { follow_path, .. } = &movement {
+            let speed = if self.path_speed > 0.0 {
+                self.path_speed
+            } else {
+                randf_range(MIN_PATH_SPEED, MAX_PATH_SPEED) as f32
+            };
+            self.current_path_speed = speed * self.archetype.speed_factor;
+            self.path_direction = 1.0;
+            follow_path.clone().set_loop(false);
+        }
+        self.movement = movement;
+
+        // set scale of the mob
+        let scale_factor =
+            randf_range(self.min_scale, self.max_scale) as f32 * self.archetype.scale_factor;
+
+        self.base_mut()
+            .set_scale(Vector3::new(scale_factor, scale_factor, scale_factor));
+
+        // set animation speed scale
+        let mut animation = self
+            .base()
+            .get_node_as::<AnimationPlayer>("AnimationPlayer");
+        animation.set_speed_scale(random_speed / self.min_speed as f32);
+    }
+
+    /// Set archetype of the mob, must be called before 'initialize'.
+    pub fn set_archetype(&mut self, archetype: &'static MobArchetype) {
+        self.archetype = archetype;
+        self.archetype_id = archetype.id.into();
+        self.health = archetype.health;
+        self.apply_tint(archetype.tint);
+    }
+
+    /// Kill the mob, or break his armor if mob needs more stomps.
+    pub fn squash(&mut self) {
+        // armored mob lose armor first
+        if self.health > 1 {
+            self.health -= 1;
+            self.apply_tint(None);
+            self.base()
+                .get_node_as::<AudioStreamPlayer>("DeadSound")
+                .play();
+
+            let health = self.health;
+            self.signals().damaged().emit(health);
+            return;
+        }
+        self.health = 0;
+
+        // start dead effect
+        self.base()
+            .get_node_as::<GpuParticles3D>("DeadEffect")
+            .set_emitting(true);
+
+        // disable collision shape
+        self.base()
+            .get_node_as::<CollisionShape3D>("CollisionShape3D")
+            .set_disabled(true);
+
+        // start dead sound
+        self.base()
+            .get_node_as::<AudioStreamPlayer>("DeadSound")
+            .play();
+
+        // hide pivot
+        self.base().get_node_as::<Node3D>("Pivot").hide();
+
+        // set velocity to zero
+        self.base_mut().set_velocity(Vector3::ZERO);
+
+        // emit signals
+        let score_value = self.archetype.score_value;
+        self.signals().squashed().emit(score_value);
+        EventBus::singleton()
+            .signals()
+            .mob_squashed()
+            .emit(score_value);
+
+        if let MobBehaviour::Splitter { child, count } = self.archetype.behaviour {
+            let position = self.base().get_position();
+            self.signals()
+                .split_requested()
+                .emit(position, &GString::from(child), count);
+        }
+    }
+
+    /// Mix color with all meshes of the mob, 'None' remove the color.
+    fn apply_tint(&mut self, tint: Option<Color>) {
+        let material = tint.map(|tint| {
+            let mut material = StandardMaterial3D::new_gd();
+            material.set_albedo(Color {
+                a: TINT_ALPHA,
+                ..tint
+            });
+            material.set_transparency(Transparency::ALPHA);
+            material.upcast::<Material>()
+        });
+
+        let meshes = self
+            .base()
+            .get_node_as::<Node3D>("Pivot")
+            .find_children_ex("*")
+            .type_("MeshInstance3D")
+            .owned(false)
+            .done();
+
+        for mesh in meshes.iter_shared() {
+            mesh.cast::<MeshInstance3D>()
+                .set_material_overlay(material.as_ref());
+        }
+    }
+
+    /// Rotate velocity of the mob to the player, but not more than 'max_angle' (in radians).
+    fn turn_to_player(&mut self, max_angle: f64) {
+        let Some(player) = self.find_player() else {
+            return;
+        };
+
+        // direction to the player
+        let position = self.base().get_global_position();
+        let to_player = player.get_global_position() - position;
+        if Vector2::new(to_player.x, to_player.z).length() < 0.01 {
+            return;
+        }
+
+        // mobs look to -Z, so angle is calculated from it
+        let target_angle = (-to_player.x as f64).atan2(-to_player.z as f64);
+        let mut rotation = self.base().get_rotation();
+        let difference = wrapf(target_angle - rotation.y as f64, -PI, PI);
+        rotation.y += difference.clamp(-max_angle, max_angle) as f32;
+        self.base_mut().set_rotation(rotation);
+
+        // rotate velocity, keep speed
+        let velocity = self.base().get_velocity();
+        let speed = Vector2::new(velocity.x, velocity.z).length();
+        let mut new_velocity = (Vector3::FORWARD * speed).rotated(Vector3::UP, rotation.y);
+        new_velocity.y = velocity.y;
+        self.base_mut().set_velocity(new_velocity);
+    }
+
+    /// Find the player on the scene.
+    fn find_player(&self) -> Option<Gd<Player>> {
+        self.base()
+            .get_tree()?
+            .get_first_node_in_group(PLAYER_GROUP)?
+            .try_cast::<Player>()
+            .ok()
+    }
+
+    /// Global position of the point on the path, if mob follows a path.
+    fn path_point(&self) -> Option<Vector3> {
+        match &self.movement {
+            MobMovement::FollowPath { follow_path, .. } => Some(follow_path.get_global_position()),
+            _ => None,
+        }
+    }
+
+    /// Return true if the player is in vision cone and not hidden by walls.
+    fn sees_player(&self, player: &Gd<Player>) -> bool {
+        let eye = self.base().get_global_position() + Vector3::UP * EYE_HEIGHT;
+        let target = player.get_global_position() + Vector3::UP * EYE_HEIGHT;
+        let to_player = target - eye;
+
+        // check vision radius
+        if to_player.length() > self.vision_radius {
+            return false;
+        }
+
+        // check vision cone, mob which already noticed the player looks all around
+        let noticed = matches!(
+            self.ai.state(),
+            AiState::Notice | AiState::Chase | AiState::Flee
+        );
+        if !noticed {
+            let forward = -self.base().get_global_basis().col_c();
+            let forward = Vector3::new(forward.x, 0.0, forward.z);
+            let horizontal = Vector3::new(to_player.x, 0.0, to_player.z);
+
+            if forward.angle_to(horizontal) > self.vision_angle.to_radians() / 2.0 {
+                return false;
+            }
+        }
+
+        // check line of sight
+        let Some(mut space) = self
+            .base()
+            .get_world_3d()
+            .and_then(|world| world.get_direct_space_state())
+        else {
+            return false;
+        };
+        let Some(query) = PhysicsRayQueryParameters3D::create_ex(eye, target)
+            .collision_mask(WORLD_COLLISION_LAYER)
+            .done()
+        else {
+            return false;
+        };
+
+        space.intersect_ray(&query).is_empty()
+    }
+
+    /// Update state of AI by what the mob sees.
+    fn update_ai(&mut self, delta: f64) -> AiState {
+        if !self.ai_enabled {
+            return AiState::Patrol;
+        }
+        let Some(path_point) = self.path_point() else {
+            return AiState::Patrol;
+        };
+
+        let position = self.base().get_global_position();
+        let player = self.find_player();
+        let perception = Perception {
+            sees_player: player
+                .as_ref()
+                .is_some_and(|player| self.sees_player(player)),
+            player_dangerous: player
+                .as_ref()
+                .is_some_and(|player| player.bind().shield_active),
+            at_path: Vector2::new(position.x - path_point.x, position.z - path_point.z).length()
+                < PATH_REACH_DISTANCE,
+        };
+        let timings = AiTimings {
+            notice_time: self.notice_time,
+            lose_sight_time: self.lose_sight_time,
+        };
+
+        self.ai.update(delta, &perception, &timings)
+    }
+
+    /// Move the mob by state of AI.
+    fn move_by_ai(&mut self, state: AiState, delta: f64) {
+        let target = match state {
+            AiState::Notice | AiState::Chase | AiState::Flee => self
+                .find_player()
+                .map(|player| player.get_global_position()),
+            AiState::ReturnToPath => self.path_point(),
+            AiState::Patrol => None,
+        };
+        let Some(target) = target else {
+            return;
+        };
+
+        match state {
+            AiState::Chase => {
+                let direction = self.route_to(target, delta);
+                self.move_in_direction(direction, self.chase_speed);
+            }
+            AiState::ReturnToPath => {
+                let direction = self.route_to(target, delta);
+                self.move_in_direction(direction, self.return_speed);
+            }
+            AiState::Flee => {
+                let direction = -self.direction_to(target);
+                self.move_in_direction(direction, self.chase_speed);
+            }
+            _ => {
+                let direction = self.direction_to(target);
+                self.move_in_direction(direction, 0.0);
+            }
+        }
+    }
+
+    /// Horizontal direction from the mob to the point, zero if mob is on the point.
+    fn direction_to(&self, point: Vector3) -> Vector3 {
+        let mut to_point = point - self.base().get_global_position();
+        to_point.y = 0.0;
+
+        if to_point.length() < 0.01 {
+            Vector3::ZERO
+        } else {
+            to_point.normalized()
+        }
+    }
+
+    /// Direction to the target, by navigation mesh if mob uses navigation.
+    fn route_to(&mut self, target: Vector3, delta: f64) -> Vector3 {
+        let Some(mut agent) = self.agent.clone() else {
+            return self.direction_to(target);
+        };
+
+        // recalculate path periodically or if target moved
+        self.repath_time += delta;
+        if self.repath_time >= REPATH_INTERVAL
+            || agent.get_target_position().distance_to(target) > REPATH_DISTANCE
+        {
+            agent.set_target_position(target);
+            self.repath_time = 0.0;
+        }
+
+        if agent.is_navigation_finished() {
+            return Vector3::ZERO;
+        }
+        self.direction_to(agent.get_next_path_position())
+    }
+
+    /// Turn the mob to direction and move him, velocity is corrected by avoidance.
+    fn move_in_direction(&mut self, direction: Vector3, speed: f32) {
+        // look in direction of move
+        if direction != Vector3::ZERO {
+            let position = self.base().get_global_position();
+            self.base_mut()
+                .look_at_from_position(position, position + direction);
+        }
+
+        // agent returns safe velocity in 'on_velocity_computed'
+        let velocity = direction * speed / self.slowdown as f32;
+        match self.agent.clone() {
+            Some(mut agent) => agent.set_velocity(velocity),
+            None => self.base_mut().set_velocity(velocity),
+        }
+    }
+
+    /// Create navigation agent with avoidance of other mobs.
+    fn create_agent(&mut self) {
+        let mut agent = NavigationAgent3D::new_alloc();
+        agent.set_name("NavigationAgent3D");
+        agent.set_path_desired_distance(0.5);
+        agent.set_target_desired_distance(0.8);
+        agent.set_avoidance_enabled(true);
+        agent.set_radius(self.avoidance_radius);
+        agent.set_max_speed(self.chase_speed);
+
+        agent
+            .signals()
+            .velocity_computed()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_velocity_computed)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        self.base_mut().add_child(&agent);
+        self.agent = Some(agent);
+    }
+
+    /// Apply velocity corrected by avoidance.
+    fn on_velocity_computed(&mut self, safe_velocity: Vector3) {
+        if self.health > 0 {
+            self.base_mut().set_velocity(safe_velocity);
+        }
+    }
+
+    /// Update hop height, mob hops every 'interval' secs.
+    fn update_hop(&mut self, delta: f64, interval: f64, height: f32) {
+        self.hop_time += delta;
+
+        // hop is a parabola from 0 to 'height' and back
+        let phase = (self.hop_time - interval) / HOP_DURATION;
+        let offset = if phase > 0.0 && phase < 1.0 {
+            4.0 * height * (phase * (1.0 - phase)) as f32
+        } else {
+            0.0
+        };
+        if phase >= 1.0 {
+            self.hop_time = 0.0;
+        }
+
+        let mut position = self.base().get_position();
+        position.y += offset - self.hop_offset;
+        self.base_mut().set_position(position);
+        self.hop_offset = offset;
+    }
+
+    /// Glow before the shot and fire at the player in range every 'interval' secs.
+    fn update_shooter(
+        &mut self,
+        delta: f64,
+        interval: f64,
+        windup: f64,
+        range: f32,
+        projectile: ProjectileSettings,
+    ) {
+        let previous_time = self.shoot_time;
+        self.shoot_time += delta;
+
+        // telegraph the shot
+        if previous_time < interval - windup && self.shoot_time >= interval - windup {
+            self.apply_tint(Some(TELEGRAPH_COLOR));
+        }
+        if self.shoot_time < interval {
+            return;
+        }
+        self.shoot_time = 0.0;
+        self.apply_tint(self.archetype.tint);
+
+        // fire only at alive player in range
+        let Some(player) = self.find_player() else {
+            return;
+        };
+        if !player.bind().is_alive() {
+            return;
+        }
+
+        let origin = self.base().get_global_position() + Vector3::UP * EYE_HEIGHT;
+        let target = player.get_global_position() + Vector3::UP * EYE_HEIGHT;
+        if origin.distance_to(target) > range {
+            return;
+        }
+
+        self.signals().projectile_requested().emit(
+            origin,
+            target - origin,
+            projectile.speed,
+            projectile.lifetime,
+            projectile.gravity,
+        );
+    }
+
+    /// Release the mob if he leave from screen.
+    fn on_visible_on_screen_notifier_3d_screen_exited(&mut self) {
+        let mob = self.to_gd();
+        self.signals().released().emit(&mob);
+    }
+
+    /// Release the mob if dead effect finalize.
+    fn on_dead_effect_finished(&mut self) {
+        let mob = self.to_gd();
+        self.signals().released().emit(&mob);
+    }
+
+    /// Stop the mob until next 'alive' call, used by pool.
+    pub fn deactivate(&mut self) {
+        // stop dead effect
+        self.base()
+            .get_node_as::<GpuParticles3D>("DeadEffect")
+            .set_emitting(false);
+
+        // disable collision shape
+        self.base()
+            .get_node_as::<CollisionShape3D>("CollisionShape3D")
+            .set_deferred("disabled", &Variant::from(true));
+
+        // set velocity to zero
+        self.base_mut().set_velocity(Vector3::ZERO);
+    }
+
+    /// Return true if mob is not squashed.
+    pub fn is_alive(&self) -> bool {
+        self.health > 0
+    }
+
+    /// Alive mob.
+    pub fn alive(&mut self) {
+        // restore health and armor
+        self.set_archetype(self.archetype);
+        self.hop_time = 0.0;
+        self.hop_offset = 0.0;
+        self.shoot_time = 0.0;
+        self.ai = MobAi::default();
+
+        // follow the path from the start again
+        if let MobMovement::FollowPath { follow_path, .. } = &self.movement {
+            follow_path.clone().set_progress(0.0);
+        }
+        self.path_direction = 1.0;
+
+        // set velocity
+        let spawn_velocity = self.spawn_velocity;
+        self.base_mut().set_velocity(spawn_velocity);
+
+        // set position
+        let spawn_coords = self.spawn_coords;
+        self.base_mut().set_position(spawn_coords);
+
+        // enable collision shape
+        self.base()
+            .get_node_as::<CollisionShape3D>("CollisionShape3D")
+            .set_deferred("disabled", &Variant::from(false));
+
+        // show pivot
+        self.base().get_node_as::<Node3D>("Pivot").show();
+    }
+}
@@ -0,0 +1,185 @@
+//! This module store math for move mobs along paths. Progress is a distance
+//! from start of the path (as in 'PathFollow3D'), it is advanced by speed
+//! in m/s and time, so movement does not depend on frame rate.
+
+use godot::prelude::*;
+
+/// This enum store what the mob does at the end of the path.
+#[derive(GodotConvert, Var, Export, Default, Clone, Copy, PartialEq, Debug)]
+#[godot(via = i64)]
+pub enum PathMode {
+    /// Start from the beginning again.
+    #[default]
+    Loop,
+
+    /// Turn back and move to the beginning.
+    PingPong,
+
+    /// Stop at the end.
+    Once,
+}
+
+/// This struct store position of the mob on the path.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PathStep {
+    /// Distance from start of the path, in m.
+    pub progress: f32,
+
+    /// Direction of move: 1 forward, -1 backward, 0 stopped.
+    pub direction: f32,
+}
+
+/// Move on the path by 'distance' (in m) and return new position.
+/// Distance may be longer than the path, for example after a long frame.
+pub fn advance(step: PathStep, distance: f32, length: f32, mode: PathMode) -> PathStep {
+    // path without length, nothing to move
+    if length <= 0.0 {
+        return PathStep {
+            progress: 0.0,
+            direction: step.direction,
+        };
+    }
+
+    match mode {
+        PathMode::Loop => PathStep {
+            progress: (step.progress + distance * step.direction).rem_euclid(length),
+            direction: step.direction,
+        },
+        PathMode::PingPong => {
+            // path there and back is a loop with double length,
+            // position on the loop is 'progress' forward and '2 * length - progress' backward
+            let cycle_length = 2.0 * length;
+            let cycle = if step.direction < 0.0 {
+                cycle_length - step.progress
+            } else {
+                step.progress
+            };
+            let cycle = (cycle + distance).rem_euclid(cycle_length);
+
+            if cycle < length {
+                PathStep {
+                    progress: cycle,
+                    direction: 1.0,
+                }
+            } else {
+                PathStep {
+                    progress: cycle_length - cycle,
+                    direction: -1.0,
+                }
+            }
+        }
+        PathMode::Once => {
+            let progress = step.progress + distance * step.direction;
+
+            // stop at the end of the path (or at the start if mob moves backward)
+            if progress >= length || (progress <= 0.0 && step.direction < 0.0) {
+                PathStep {
+                    progress: progress.clamp(0.0, length),
+                    direction: 0.0,
+                }
+            } else {
+                PathStep {
+                    progress,
+                    direction: step.direction,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LENGTH: f32 = 10.0;
+
+    fn step(progress: f32, direction: f32) -> PathStep {
+        PathStep {
+            progress,
+            direction,
+        }
+    }
+
+    fn assert_step(actual: PathStep, expected: PathStep) {
+        assert!(
+            (actual.progress - expected.progress).abs() < 1e-4
+                && actual.direction == expected.direction,
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+
+    #[test]
+    fn loop_moves_forward() {
+        let result = advance(step(2.0, 1.0), 3.0, LENGTH, PathMode::Loop);
+        assert_step(result, step(5.0, 1.0));
+    }
+
+    #[test]
+    fn loop_wraps_at_the_end() {
+        let result = advance(step(8.0, 1.0), 5.0, LENGTH, PathMode::Loop);
+        assert_step(result, step(3.0, 1.0));
+    }
+
+    #[test]
+    fn loop_wraps_distance_longer_than_path() {
+        let result = advance(step(1.0, 1.0), 25.0, LENGTH, PathMode::Loop);
+        assert_step(result, step(6.0, 1.0));
+    }
+
+    #[test]
+    fn ping_pong_turns_back_at_the_end() {
+        let result = advance(step(8.0, 1.0), 5.0, LENGTH, PathMode::PingPong);
+        assert_step(result, step(7.0, -1.0));
+    }
+
+    #[test]
+    fn ping_pong_turns_forward_at_the_start() {
+        let result = advance(step(2.0, -1.0), 5.0, LENGTH, PathMode::PingPong);
+        assert_step(result, step(3.0, 1.0));
+    }
+
+    #[test]
+    fn ping_pong_moves_backward() {
+        let result = advance(step(6.0, -1.0), 2.0, LENGTH, PathMode::PingPong);
+        assert_step(result, step(4.0, -1.0));
+    }
+
+    #[test]
+    fn ping_pong_handles_several_turns() {
+        let result = advance(step(0.0, 1.0), 25.0, LENGTH, PathMode::PingPong);
+        assert_step(result, step(5.0, 1.0));
+    }
+
+    #[test]
+    fn once_stops_at_the_end() {
+        let result = advance(step(8.0, 1.0), 5.0, LENGTH, PathMode::Once);
+        assert_step(result, step(LENGTH, 0.0));
+
+        let result = advance(result, 5.0, LENGTH, PathMode::Once);
+        assert_step(result, step(LENGTH, 0.0));
+    }
+
+    #[test]
+    fn once_moves_before_the_end() {
+        let result = advance(step(1.0, 1.0), 2.5, LENGTH, PathMode::Once);
+        assert_step(result, step(3.5, 1.0));
+    }
+
+    #[test]
+    fn distance_is_speed_multiplied_by_time() {
+        // 4 m/s for 0.5 secs in 30 frames or in 1 frame gives same progress
+        let mut many_frames = step(0.0, 1.0);
+        for _ in 0..30 {
+            many_frames = advance(many_frames, 4.0 * (0.5 / 30.0), LENGTH, PathMode::Loop);
+        }
+        let one_frame = advance(step(0.0, 1.0), 4.0 * 0.5, LENGTH, PathMode::Loop);
+
+        assert_step(many_frames, one_frame);
+    }
+
+    #[test]
+    fn empty_path_does_not_move() {
+        let result = advance(step(0.0, 1.0), 5.0, 0.0, PathMode::PingPong);
+        assert_step(result, step(0.0, 1.0));
+    }
+}
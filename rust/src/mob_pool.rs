@@ -0,0 +1,226 @@
+//! This module store a pool of mobs. Instead of create a new mob for every
+//! spawn and delete him after death, mobs are returned to the pool and
+//! reused. Pool also limit how much mobs can be alive at the same time.
+
+use crate::mob::Mob;
+use godot::{
+    classes::{INode, node::ProcessMode, object::ConnectFlags},
+    obj::WithBaseField,
+    prelude::*,
+};
+
+/// This class create, store and reuse mobs.
+/// Active mobs are children of the pool, so pool must not be a 'Node3D'.
+#[derive(GodotClass)]
+#[class(base = Node)]
+pub struct MobPool {
+    /// Mob scene for create new mobs.
+    mob_scene: OnReady<Gd<PackedScene>>,
+
+    /// Maximum amount of active mobs, 0 means no limit.
+    #[export]
+    pub max_active_mobs: i64,
+
+    /// How much mobs create on ready.
+    #[export]
+    pub prewarm_count: i64,
+
+    /// Mobs which are spawned now.
+    active_mobs: Vec<Gd<Mob>>,
+
+    /// Mobs which are ready for reuse.
+    free_mobs: Vec<Gd<Mob>>,
+
+    /// How much mobs created by pool.
+    created_count: i64,
+
+    /// How much times mob was reused.
+    reused_count: i64,
+
+    /// How much spawns was rejected because of limit.
+    rejected_count: i64,
+
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for MobPool {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            mob_scene: OnReady::from_loaded("res://scenes/objecst/mob.tscn"),
+            max_active_mobs: 40,
+            prewarm_count: 0,
+            active_mobs: Vec::new(),
+            free_mobs: Vec::new(),
+            created_count: 0,
+            reused_count: 0,
+            rejected_count: 0,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        // create mobs before game start
+        for _ in 0..self.prewarm_count {
+            let mut mob = self.create_mob();
+            Self::deactivate(&mut mob);
+            self.free_mobs.push(mob);
+        }
+    }
+}
+
+#[godot_api]
+impl MobPool {
+    /// Emitted if any mob from pool is squashed.
+    #[signal]
+    pub fn mob_squashed(score_value: i64);
+
+    /// Emitted if splitter from pool is squashed.
+    #[signal]
+    pub fn split_requested(position: Vector3, archetype_id: GString, count: i64);
+
+    /// Emitted if shooter from pool fires.
+    #[signal]
+    pub fn projectile_requested(
+        position: Vector3,
+        direction: Vector3,
+        speed: f32,
+        lifetime: f64,
+        gravity: f32,
+    );
+
+    /// Get mob from pool or create a new one.
+    /// Return 'None' if limit of active mobs is reached.
+    /// Mob must be revived by 'Mob::alive' after set spawn parameters.
+    pub fn acquire(&mut self) -> Option<Gd<Mob>> {
+        if self.max_active_mobs > 0 && self.active_mobs.len() as i64 >= self.max_active_mobs {
+            self.rejected_count += 1;
+            return None;
+        }
+
+        let mut mob = match self.free_mobs.pop() {
+            Some(mob) => {
+                self.reused_count += 1;
+                mob
+            }
+            None => self.create_mob(),
+        };
+
+        // enable mob
+        mob.set_process_mode(ProcessMode::INHERIT);
+        mob.show();
+
+        self.active_mobs.push(mob.clone());
+        Some(mob)
+    }
+
+    /// Return mob to the pool.
+    pub fn release(&mut self, mut mob: Gd<Mob>) {
+        // mob can be released twice (dead effect and screen exit)
+        let Some(index) = self.active_mobs.iter().position(|active| *active == mob) else {
+            return;
+        };
+        self.active_mobs.swap_remove(index);
+
+        Self::deactivate(&mut mob);
+        self.free_mobs.push(mob);
+    }
+
+    /// Return all active mobs to the pool.
+    pub fn release_all(&mut self) {
+        for mut mob in std::mem::take(&mut self.active_mobs) {
+            Self::deactivate(&mut mob);
+            self.free_mobs.push(mob);
+        }
+    }
+
+    /// Amount of mobs which are spawned now.
+    #[func]
+    pub fn get_active_count(&self) -> i64 {
+        self.active_mobs.len() as i64
+    }
+
+    /// Amount of mobs which are ready for reuse.
+    #[func]
+    pub fn get_free_count(&self) -> i64 {
+        self.free_mobs.len() as i64
+    }
+
+    /// Amount of mobs created by pool.
+    #[func]
+    pub fn get_created_count(&self) -> i64 {
+        self.created_count
+    }
+
+    /// How much times mob was reused.
+    #[func]
+    pub fn get_reused_count(&self) -> i64 {
+        self.reused_count
+    }
+
+    /// How much spawns was rejected because of limit.
+    #[func]
+    pub fn get_rejected_count(&self) -> i64 {
+        self.rejected_count
+    }
+
+    /// Create a new mob, add him to the pool and connect his signals.
+    fn create_mob(&mut self) -> Gd<Mob> {
+        let mut mob = self.mob_scene.instantiate_as::<Mob>();
+        self.created_count += 1;
+
+        // release is deferred, because mob emit the signal from his own method
+        mob.signals()
+            .released()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::release)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        mob.signals()
+            .squashed()
+            .connect_obj(&self.to_gd(), Self::on_mob_squashed);
+
+        mob.signals()
+            .split_requested()
+            .connect_obj(&self.to_gd(), Self::on_mob_split_requested);
+
+        mob.signals()
+            .projectile_requested()
+            .connect_obj(&self.to_gd(), Self::on_mob_projectile_requested);
+
+        self.base_mut().add_child(&mob);
+        mob
+    }
+
+    /// Disable mob until next use.
+    fn deactivate(mob: &mut Gd<Mob>) {
+        mob.bind_mut().deactivate();
+        mob.hide();
+        mob.set_process_mode(ProcessMode::DISABLED);
+    }
+
+    fn on_mob_squashed(&mut self, score_value: i64) {
+        self.signals().mob_squashed().emit(score_value);
+    }
+
+    fn on_mob_split_requested(&mut self, position: Vector3, archetype_id: GString, count: i64) {
+        self.signals()
+            .split_requested()
+            .emit(position, &archetype_id, count);
+    }
+
+    fn on_mob_projectile_requested(
+        &mut self,
+        position: Vector3,
+        direction: Vector3,
+        speed: f32,
+        lifetime: f64,
+        gravity: f32,
+    ) {
+        self.signals()
+            .projectile_requested()
+            .emit(position, direction, speed, lifetime, gravity);
+    }
+}
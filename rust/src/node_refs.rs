@@ -0,0 +1,115 @@
+//! This module store a startup check of node references. Classes keep typed
+//! references to required nodes of their scene ('OnReady' fields), paths of
+//! them are exported, so they can be changed in editor. All references of
+//! the scene are resolved in 'ready' and checked in one pass by the scene
+//! root: if the scene is restructured, all missing and mistyped nodes are
+//! reported in one error and the scene is not started instead of panic on
+//! the first node. Optional nodes may be missing, only their class is checked.
+
+use godot::{classes::node::ProcessMode, prelude::*};
+
+/// Find node by path relative to owner and check his class.
+pub fn find_node<T: GodotClass + Inherits<Node>>(
+    owner: &Gd<Node>,
+    path: &str,
+) -> Result<Gd<T>, String> {
+    let Some(node) = owner.get_node_or_null(path) else {
+        return Err(format!("node is missing (expected '{}')", T::class_name()));
+    };
+
+    node.try_cast::<T>().map_err(|node| {
+        format!(
+            "expected '{}', found '{}'",
+            T::class_name(),
+            node.get_class()
+        )
+    })
+}
+
+/// This struct resolve node references of one class and collect errors.
+pub struct NodeRefs {
+    /// Node which owns references, paths are relative to him.
+    owner: Gd<Node>,
+
+    /// Errors of all references, with path.
+    errors: Vec<String>,
+}
+
+impl NodeRefs {
+    pub fn new(owner: Gd<Node>) -> Self {
+        Self {
+            owner,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Resolve reference to node at path, add error if node is missing or has wrong class.
+    /// Return resolved node, so references of the child can be included.
+    pub fn resolve<T: GodotClass + Inherits<Node>>(
+        &mut self,
+        reference: &mut OnReady<Gd<T>>,
+        path: &NodePath,
+    ) -> Option<Gd<T>> {
+        match find_node::<T>(&self.owner, &path.to_string()) {
+            Ok(node) => {
+                reference.init(node.clone());
+                Some(node)
+            }
+            Err(error) => {
+                let owner = self.owner.get_name();
+                self.errors.push(format!("'{owner}/{path}': {error}"));
+                None
+            }
+        }
+    }
+
+    /// Resolve reference to optional node at path, empty path or missing node give 'None',
+    /// add error if node has wrong class.
+    pub fn resolve_optional<T: GodotClass + Inherits<Node>>(
+        &mut self,
+        path: &NodePath,
+    ) -> Option<Gd<T>> {
+        if path.is_empty() || !self.owner.has_node(path) {
+            return None;
+        }
+
+        match find_node::<T>(&self.owner, &path.to_string()) {
+            Ok(node) => Some(node),
+            Err(error) => {
+                let owner = self.owner.get_name();
+                self.errors.push(format!("'{owner}/{path}': {error}"));
+                None
+            }
+        }
+    }
+
+    /// Add errors of references of a child, they are reported with errors of the owner.
+    pub fn include(&mut self, errors: &[String]) {
+        self.errors.extend_from_slice(errors);
+    }
+
+    /// Return errors for the scene root, the owner is disabled if any reference is invalid.
+    pub fn into_errors(mut self) -> Vec<String> {
+        if !self.errors.is_empty() {
+            self.owner.set_process_mode(ProcessMode::DISABLED);
+        }
+        self.errors
+    }
+
+    /// Report all errors of the scene at once and disable the scene (the owner is
+    /// the scene root), return true if all references are resolved.
+    pub fn finish(mut self) -> bool {
+        if self.errors.is_empty() {
+            return true;
+        }
+
+        godot_error!(
+            "scene '{}' has {} invalid node references and is not started:\n{}",
+            self.owner.get_name(),
+            self.errors.len(),
+            self.errors.join("\n")
+        );
+        self.owner.set_process_mode(ProcessMode::DISABLED);
+        false
+    }
+}
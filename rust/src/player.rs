@@ -2,16 +2,61 @@
 //! Player is a entity with control by gamer. It is needed so that
 //! the gamer can connect with the gaming world.
 
+#[cfg(feature = "dev-tools")]
+use crate::bot::Bot;
+use crate::{
+    event_bus::EventBus,
+    main_scene::Improvements,
+    mob::MOB_GROUP,
+    node_refs::NodeRefs,
+    replay::{InputFrame, ReplayState},
+};
 use godot::{
     classes::{
         AnimationPlayer, Area3D, AudioStreamPlayer, CharacterBody3D, GpuParticles3D,
-        ICharacterBody3D, Input,
+        ICharacterBody3D, Input, MeshInstance3D,
     },
     obj::WithBaseField,
     prelude::*,
 };
 use std::f32::consts::PI;
 
+/// Group of the player.
+pub const PLAYER_GROUP: &str = "player";
+
+/// Collision layer of the player.
+pub const PLAYER_COLLISION_LAYER: u32 = 1;
+
+/// Minimal dot product of up and collision normal to squash the mob.
+const STOMP_MIN_DOT: f32 = 0.1;
+
+/// Return true if collision with normal hits the mob from above.
+pub fn is_stomp(normal: Vector3) -> bool {
+    Vector3::UP.dot(normal) > STOMP_MIN_DOT
+}
+
+/// This enum store all reasons of the player death.
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum DeathCause {
+    #[default]
+    Creep,
+    Spike,
+    Fall,
+    Projectile,
+}
+
+impl DeathCause {
+    /// Id of death cause for logs and CSV.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Creep => "creep",
+            Self::Spike => "spike",
+            Self::Fall => "fall",
+            Self::Projectile => "projectile",
+        }
+    }
+}
+
 /// Player class store a logic for control player and other.
 #[derive(GodotClass)]
 #[class(base = CharacterBody3D)]
@@ -22,9 +67,26 @@ pub struct Player {
     /// Shield around player, if he active player can't die.
     pub shield_active: bool,
 
+    /// God mode from developer console, player can't die and returns to spawn after fall.
+    #[cfg(feature = "dev-tools")]
+    pub god_mode: bool,
+
     /// Indicates whether the player is dead or not.
     is_die: bool,
 
+    /// Reason of the last death.
+    pub death_cause: DeathCause,
+
+    /// Indicates whether the player moved in previous frame or not.
+    was_moving: bool,
+
+    /// Recording or playback of input.
+    pub replay: ReplayState,
+
+    /// Bot which plays instead of the gamer, 'None' if the gamer plays.
+    #[cfg(feature = "dev-tools")]
+    pub bot: Option<Bot>,
+
     /// Gravity for player.
     #[export]
     pub fall_acceleration: f64,
@@ -39,11 +101,44 @@ pub struct Player {
 
     /// How fast the player moves, in m/s.
     #[export]
-    speed: f64,
+    pub speed: f64,
 
     /// Position for spawn if player alive.
     #[export]
-    spawn_coords: Vector3,
+    pub spawn_coords: Vector3,
+
+    /// Model of the player, it is rotated to direction of movement.
+    #[export]
+    pivot_path: NodePath,
+    pivot: OnReady<Gd<Node3D>>,
+
+    /// Visual of the shield.
+    #[export]
+    shield_path: NodePath,
+    shield: OnReady<Gd<MeshInstance3D>>,
+
+    /// Animation of the model.
+    #[export]
+    animation_player_path: NodePath,
+    animation_player: OnReady<Gd<AnimationPlayer>>,
+
+    /// Area which detects enemies.
+    #[export]
+    mob_detector_path: NodePath,
+    mob_detector: OnReady<Gd<Area3D>>,
+
+    /// Sound of the death.
+    #[export]
+    death_sound_path: NodePath,
+    death_sound: OnReady<Gd<AudioStreamPlayer>>,
+
+    /// Particles of the death.
+    #[export]
+    death_effect_path: NodePath,
+    death_effect: OnReady<Gd<GpuParticles3D>>,
+
+    /// Errors of node references, they are reported by the scene root.
+    invalid_refs: Vec<String>,
 
     base: Base<CharacterBody3D>,
 }
@@ -54,26 +149,64 @@ impl ICharacterBody3D for Player {
         Self {
             target_velocity: Vector3::ZERO,
             shield_active: false,
+            #[cfg(feature = "dev-tools")]
+            god_mode: false,
             is_die: true,
+            death_cause: DeathCause::Creep,
+            was_moving: false,
+            replay: ReplayState::Off,
+            #[cfg(feature = "dev-tools")]
+            bot: None,
             fall_acceleration: 75.0,
             bounce_impulse: 16.0,
             jump_impulse: 20.0,
             speed: 14.0,
             spawn_coords: Vector3::ZERO,
+            pivot_path: "Pivot".into(),
+            pivot: OnReady::manual(),
+            shield_path: "Pivot/Shield".into(),
+            shield: OnReady::manual(),
+            animation_player_path: "AnimationPlayer".into(),
+            animation_player: OnReady::manual(),
+            mob_detector_path: "MobDetector".into(),
+            mob_detector: OnReady::manual(),
+            death_sound_path: "DeathSound".into(),
+            death_sound: OnReady::manual(),
+            death_effect_path: "DeathEffect".into(),
+            death_effect: OnReady::manual(),
+            invalid_refs: Vec::new(),
             base
         }
     }
 
     fn ready(&mut self) {
+        // resolve nodes of the scene
+        let mut refs = NodeRefs::new(self.to_gd().upcast());
+        refs.resolve(&mut self.pivot, &self.pivot_path);
+        refs.resolve(&mut self.shield, &self.shield_path);
+        refs.resolve(&mut self.animation_player, &self.animation_player_path);
+        refs.resolve(&mut self.mob_detector, &self.mob_detector_path);
+        refs.resolve(&mut self.death_sound, &self.death_sound_path);
+        refs.resolve(&mut self.death_effect, &self.death_effect_path);
+        self.invalid_refs = refs.into_errors();
+        if !self.invalid_refs.is_empty() {
+            return;
+        }
+
         // hide Pivot
-        self.base().get_node_as::<Node3D>("Pivot").hide();
+        self.pivot.hide();
 
         // connect 'body_entered' signal
-        self.base()
-            .get_node_as::<Area3D>("MobDetector")
+        self.mob_detector
             .signals()
             .body_entered()
             .connect_obj(self, Self::on_mob_detector_body_entered);
+
+        // shield is activated by power-up
+        EventBus::singleton()
+            .signals()
+            .power_up_changed()
+            .connect_obj(self, Self::on_power_up_changed);
     }
 
     fn physics_process(&mut self, delta: f64) {
@@ -82,45 +215,49 @@ impl ICharacterBody3D for Player {
             return;
         }
 
-        // get input singleton
-        let input = Input::singleton();
+        // get input of this tick, from devices (or bot) or from replay
+        let live_input = self.live_input();
+        let input = self.replay.next_frame(|| live_input);
 
         // variable to store the input direction
         let mut direction = Vector3::ZERO;
 
         // we check for each move input and update the direction accordingly
-        if input.is_action_pressed("move_right") {
+        if input.contains(InputFrame::MOVE_RIGHT) {
             direction.x += 1.0;
         }
-        if input.is_action_pressed("move_left") {
+        if input.contains(InputFrame::MOVE_LEFT) {
             direction.x -= 1.0;
         }
-        if input.is_action_pressed("move_back") {
+        if input.contains(InputFrame::MOVE_BACK) {
             direction.z += 1.0;
         }
-        if input.is_action_pressed("move_forward") {
+        if input.contains(InputFrame::MOVE_FORWARD) {
             direction.z -= 1.0;
         }
 
+        // notify if player started moving
+        let is_moving = direction != Vector3::ZERO;
+        if is_moving && !self.was_moving {
+            self.signals().started_moving().emit();
+        }
+        self.was_moving = is_moving;
+
         // update look and normalize direction if need
         // and set animation speed
-        if direction != Vector3::ZERO {
+        if is_moving {
             // normalize direction
             direction = direction.normalized();
 
             // set look at for Pivot
             let point = self.base().get_position() + direction;
-            self.base().get_node_as::<Node3D>("Pivot").look_at(point);
+            self.pivot.look_at(point);
 
             // set animation speed
-            self.base()
-                .get_node_as::<AnimationPlayer>("AnimationPlayer")
-                .set_speed_scale(3.0);
+            self.animation_player.set_speed_scale(3.0);
         } else {
             // set animation speed
-            self.base()
-                .get_node_as::<AnimationPlayer>("AnimationPlayer")
-                .set_speed_scale(1.0);
+            self.animation_player.set_speed_scale(1.0);
         }
 
         // update ground velocity
@@ -134,8 +271,9 @@ impl ICharacterBody3D for Player {
         }
 
         // jumping
-        if self.base().is_on_floor() && input.is_action_pressed("jump") {
+        if self.base().is_on_floor() && input.contains(InputFrame::JUMP) {
             self.target_velocity.y = self.jump_impulse as f32;
+            self.signals().jumped().emit();
         }
 
         // iterate through all collisions that occurred this frame
@@ -158,13 +296,13 @@ impl ICharacterBody3D for Player {
                 .get_collider()
                 .unwrap()
                 .cast::<Node3D>()
-                .is_in_group("mob")
+                .is_in_group(MOB_GROUP)
             {
                 let collider = collision.get_collider().unwrap();
                 let mut mob = collider.cast::<crate::mob::Mob>();
 
                 // we check that we are hitting it from above
-                if Vector3::UP.dot(collision.get_normal()) > 0.1 {
+                if is_stomp(collision.get_normal()) {
                     // if so, we squash it and bounce
                     self.target_velocity.y = self.bounce_impulse as f32;
                     mob.bind_mut().squash();
@@ -182,18 +320,23 @@ impl ICharacterBody3D for Player {
 
         // get rotation
         let velocity = self.base().get_velocity();
-        let mut rotation = self.base().get_node_as::<Node3D>("Pivot").get_rotation();
+        let mut rotation = self.pivot.get_rotation();
         rotation.x = PI / 6.0 * velocity.y / self.jump_impulse as f32;
 
         // rotate
-        self.base_mut()
-            .get_node_as::<Node3D>("Pivot")
-            .set_rotation(rotation);
+        self.pivot.set_rotation(rotation);
 
-        // ! NEXT CODE ONLY FOR DEVELOP!
-        // if player.y position < -10, then set player position to spawn_coords
+        // player dies if he falls below the level, in god mode he returns to spawn
         if self.base().get_position().y < -10.0 {
-            self.kill();
+            #[cfg(feature = "dev-tools")]
+            if self.god_mode {
+                let spawn_coords = self.spawn_coords;
+                self.target_velocity = Vector3::ZERO;
+                self.base_mut().set_position(spawn_coords);
+                return;
+            }
+
+            self.kill(DeathCause::Fall);
         }
     }
 }
@@ -204,6 +347,14 @@ impl Player {
     #[signal]
     pub fn hit();
 
+    /// Signal emit if player started moving after standing.
+    #[signal]
+    pub fn started_moving();
+
+    /// Signal emit if player jumped from the floor.
+    #[signal]
+    pub fn jumped();
+
     /// Makes the player alive.
     #[func]
     pub fn alive(&mut self) {
@@ -211,38 +362,79 @@ impl Player {
         self.is_die = false;
 
         // show Pivot
-        self.base().get_node_as::<Node3D>("Pivot").show();
+        self.pivot.show();
 
         // set position to spawn coordinates
         let spawn_coords = self.spawn_coords;
         self.base_mut().set_position(spawn_coords);
     }
 
+    /// Errors of node references, empty if all nodes are resolved.
+    pub fn invalid_refs(&self) -> &[String] {
+        &self.invalid_refs
+    }
+
+    /// Return true if player is alive.
+    pub fn is_alive(&self) -> bool {
+        !self.is_die
+    }
+
     /// Kill the player (from signal).
-    fn on_mob_detector_body_entered(&mut self, _body: Gd<Node3D>) {
-        self.kill();
+    fn on_mob_detector_body_entered(&mut self, body: Gd<Node3D>) {
+        // all enemies without 'mob' group are spikes
+        if body.is_in_group(MOB_GROUP) {
+            self.kill(DeathCause::Creep);
+        } else {
+            self.kill(DeathCause::Spike);
+        }
+    }
+
+    /// Return true if player can't die, with shield (or in god mode).
+    fn is_invulnerable(&self) -> bool {
+        #[cfg(feature = "dev-tools")]
+        if self.god_mode {
+            return true;
+        }
+
+        self.shield_active
+    }
+
+    /// Input from devices (or from bot, if it plays).
+    fn live_input(&self) -> InputFrame {
+        #[cfg(feature = "dev-tools")]
+        if let Some(bot) = self.bot.as_ref() {
+            return bot.next_frame(self);
+        }
+
+        InputFrame::read(&Input::singleton())
     }
 
     /// Kill the player.
-    fn kill(&mut self) {
-        if !self.is_die && !self.shield_active {
+    pub fn kill(&mut self, cause: DeathCause) {
+        if !self.is_die && !self.is_invulnerable() {
             self.is_die = true;
+            self.death_cause = cause;
 
             // hide Pivot
-            self.base().get_node_as::<Node3D>("Pivot").hide();
+            self.pivot.hide();
 
             // play DeadSound
-            self.base()
-                .get_node_as::<AudioStreamPlayer>("DeathSound")
-                .play();
+            self.death_sound.play();
 
             // emit DeadEffect
-            self.base()
-                .get_node_as::<GpuParticles3D>("DeathEffect")
-                .set_emitting(true);
+            self.death_effect.set_emitting(true);
 
-            // emit signal
+            // emit signals
             self.signals().hit().emit();
+            EventBus::singleton().signals().player_hit().emit();
         }
     }
+
+    /// Activate or deactivate shield on power-up change.
+    fn on_power_up_changed(&mut self, power_up: Improvements) {
+        self.shield_active = power_up == Improvements::PlayerShield;
+
+        let shield_active = self.shield_active;
+        self.shield.set_visible(shield_active);
+    }
 }
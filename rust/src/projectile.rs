@@ -0,0 +1,138 @@
+//! This module store a projectile, which is fired by ranged creeps.
+//! Projectile flies with speed and gravity, kills the player on hit and
+//! disappears on hit with the world, after lifetime or on the player shield.
+
+use crate::player::{DeathCause, Player};
+use godot::{
+    classes::{
+        Area3D, CollisionShape3D, IArea3D, MeshInstance3D, SphereMesh, SphereShape3D,
+        StandardMaterial3D,
+    },
+    obj::WithBaseField,
+    prelude::*,
+};
+
+/// Radius of projectile, in m.
+const PROJECTILE_RADIUS: f32 = 0.25;
+
+/// Color of projectile.
+const PROJECTILE_COLOR: Color = Color::from_rgb(0.2, 0.9, 1.0);
+
+/// This struct describe how projectile flies.
+#[derive(Clone, Copy)]
+pub struct ProjectileSettings {
+    /// Speed of projectile, in m/s.
+    pub speed: f32,
+
+    /// How long projectile flies, in secs.
+    pub lifetime: f64,
+
+    /// Gravity acceleration, in m/s^2 (0 means a straight line).
+    pub gravity: f32,
+}
+
+/// This class is a projectile, it is created and reused by 'ProjectilePool'.
+#[derive(GodotClass)]
+#[class(base = Area3D)]
+pub struct Projectile {
+    /// Current velocity, in m/s.
+    velocity: Vector3,
+
+    /// Gravity acceleration, in m/s^2.
+    gravity: f32,
+
+    /// Time left before projectile disappears, in secs.
+    time_left: f64,
+
+    base: Base<Area3D>,
+}
+
+#[godot_api]
+impl IArea3D for Projectile {
+    fn init(base: Base<Area3D>) -> Self {
+        Self {
+            velocity: Vector3::ZERO,
+            gravity: 0.0,
+            time_left: 0.0,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        // create collision shape
+        let mut shape = SphereShape3D::new_gd();
+        shape.set_radius(PROJECTILE_RADIUS);
+
+        let mut collision = CollisionShape3D::new_alloc();
+        collision.set_shape(&shape);
+        self.base_mut().add_child(&collision);
+
+        // create mesh
+        let mut material = StandardMaterial3D::new_gd();
+        material.set_albedo(PROJECTILE_COLOR);
+        material.set_emission(PROJECTILE_COLOR);
+
+        let mut mesh = SphereMesh::new_gd();
+        mesh.set_radius(PROJECTILE_RADIUS);
+        mesh.set_height(PROJECTILE_RADIUS * 2.0);
+        mesh.set_material(&material);
+
+        let mut mesh_instance = MeshInstance3D::new_alloc();
+        mesh_instance.set_mesh(&mesh);
+        self.base_mut().add_child(&mesh_instance);
+
+        // projectile is not detected by other areas
+        self.base_mut().set_monitorable(false);
+
+        // connect method for body_entered signal
+        self.signals()
+            .body_entered()
+            .connect_self(Self::on_body_entered);
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        // release projectile after lifetime
+        self.time_left -= delta;
+        if self.time_left <= 0.0 {
+            self.release();
+            return;
+        }
+
+        // fly with gravity
+        self.velocity.y -= self.gravity * delta as f32;
+        let position = self.base().get_global_position() + self.velocity * delta as f32;
+        self.base_mut().set_global_position(position);
+    }
+}
+
+#[godot_api]
+impl Projectile {
+    /// Emitted if projectile is not needed anymore, owner returns him to the pool.
+    #[signal]
+    pub fn released(projectile: Gd<Projectile>);
+
+    /// Start flight from position in direction.
+    pub fn fire(&mut self, position: Vector3, direction: Vector3, settings: ProjectileSettings) {
+        self.velocity = direction.normalized_or_zero() * settings.speed;
+        self.gravity = settings.gravity;
+        self.time_left = settings.lifetime;
+        self.base_mut().set_global_position(position);
+    }
+
+    /// Kill the player on hit, shield of the player destroys projectile.
+    fn on_body_entered(&mut self, body: Gd<Node3D>) {
+        if let Ok(mut player) = body.try_cast::<Player>() {
+            // shield protects the player ('kill' does nothing with shield)
+            player.bind_mut().kill(DeathCause::Projectile);
+        }
+
+        // projectile disappears on any hit (player, shield or world)
+        self.release();
+    }
+
+    /// Emit 'released' signal.
+    fn release(&mut self) {
+        let projectile = self.to_gd();
+        self.signals().released().emit(&projectile);
+    }
+}
@@ -0,0 +1,156 @@
+//! This module store a pool of projectiles. Projectiles are returned to
+//! the pool after hit or lifetime and reused for next shots.
+
+use crate::{
+    mob::WORLD_COLLISION_LAYER,
+    player::PLAYER_COLLISION_LAYER,
+    projectile::{Projectile, ProjectileSettings},
+};
+use godot::{
+    classes::{INode, node::ProcessMode, object::ConnectFlags},
+    obj::WithBaseField,
+    prelude::*,
+};
+
+/// This class create, store and reuse projectiles.
+/// Active projectiles are children of the pool, so pool must not be a 'Node3D'.
+#[derive(GodotClass)]
+#[class(base = Node)]
+pub struct ProjectilePool {
+    /// Maximum amount of active projectiles, 0 means no limit.
+    #[export]
+    pub max_active_projectiles: i64,
+
+    /// Collision layers which projectiles hit (the player and the world by default).
+    #[export(flags_3d_physics)]
+    pub collision_mask: u32,
+
+    /// Projectiles which fly now.
+    active_projectiles: Vec<Gd<Projectile>>,
+
+    /// Projectiles which are ready for reuse.
+    free_projectiles: Vec<Gd<Projectile>>,
+
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for ProjectilePool {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            max_active_projectiles: 30,
+            collision_mask: PLAYER_COLLISION_LAYER | WORLD_COLLISION_LAYER,
+            active_projectiles: Vec::new(),
+            free_projectiles: Vec::new(),
+            base
+        }
+    }
+}
+
+#[godot_api]
+impl ProjectilePool {
+    /// Fire projectile from position in direction.
+    /// Return 'false' if limit of active projectiles is reached.
+    pub fn fire(
+        &mut self,
+        position: Vector3,
+        direction: Vector3,
+        settings: ProjectileSettings,
+    ) -> bool {
+        if self.max_active_projectiles > 0
+            && self.active_projectiles.len() as i64 >= self.max_active_projectiles
+        {
+            return false;
+        }
+
+        let mut projectile = match self.free_projectiles.pop() {
+            Some(projectile) => projectile,
+            None => self.create_projectile(),
+        };
+
+        // enable projectile
+        let collision_mask = self.collision_mask;
+        projectile.set_collision_mask(collision_mask);
+        projectile.set_process_mode(ProcessMode::INHERIT);
+        projectile.set_deferred("monitoring", &Variant::from(true));
+        projectile.show();
+        projectile.bind_mut().fire(position, direction, settings);
+
+        self.active_projectiles.push(projectile);
+        true
+    }
+
+    /// Fire projectile requested by shooter ('projectile_requested' signal of 'Mob').
+    pub fn on_projectile_requested(
+        &mut self,
+        position: Vector3,
+        direction: Vector3,
+        speed: f32,
+        lifetime: f64,
+        gravity: f32,
+    ) {
+        let settings = ProjectileSettings {
+            speed,
+            lifetime,
+            gravity,
+        };
+        self.fire(position, direction, settings);
+    }
+
+    /// Return projectile to the pool.
+    pub fn release(&mut self, mut projectile: Gd<Projectile>) {
+        // projectile can be released twice (hit and lifetime in one frame)
+        let Some(index) = self
+            .active_projectiles
+            .iter()
+            .position(|active| *active == projectile)
+        else {
+            return;
+        };
+        self.active_projectiles.swap_remove(index);
+
+        Self::deactivate(&mut projectile);
+        self.free_projectiles.push(projectile);
+    }
+
+    /// Return all active projectiles to the pool.
+    pub fn release_all(&mut self) {
+        for mut projectile in std::mem::take(&mut self.active_projectiles) {
+            Self::deactivate(&mut projectile);
+            self.free_projectiles.push(projectile);
+        }
+    }
+
+    /// Amount of projectiles which fly now.
+    #[func]
+    pub fn get_active_count(&self) -> i64 {
+        self.active_projectiles.len() as i64
+    }
+
+    /// Create a new projectile, add him to the pool and connect his signals.
+    fn create_projectile(&mut self) -> Gd<Projectile> {
+        let mut projectile = Projectile::new_alloc();
+        projectile.set_name(&format!("Projectile{}", self.base().get_child_count()));
+        projectile.set_collision_layer(0);
+
+        // release is deferred, because projectile emit the signal from his own method
+        projectile
+            .signals()
+            .released()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::release)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        self.base_mut().add_child(&projectile);
+        projectile
+    }
+
+    /// Disable projectile until next use.
+    fn deactivate(projectile: &mut Gd<Projectile>) {
+        projectile.set_deferred("monitoring", &Variant::from(false));
+        projectile.hide();
+        projectile.set_process_mode(ProcessMode::DISABLED);
+    }
+}
@@ -0,0 +1,369 @@
+//! This module store a recording and playback of runs. Replay store the
+//! seed of random generator, id of the level and input of the player for
+//! every physics tick, so the run can be played again frame-for-frame.
+//!
+//! Last run of every level is saved to 'user://replays/<level id>.replay'.
+//! Replay is played back if game is started with path to it:
+//! ```sh
+//! godot --path godot -- --replay=user://replays/arena.replay
+//! ```
+//!
+//! Binary format (little endian):
+//! - magic 'SQRP' and version (u8);
+//! - seed (u64), length of level id (u16) and level id (UTF-8);
+//! - amount of runs (u32), every run is amount of ticks (u16) and input flags (u8).
+
+use crate::player::Player;
+use godot::{
+    classes::{DirAccess, FileAccess, Input, Os, file_access::ModeFlags},
+    global::{Error, randi, seed},
+    prelude::*,
+};
+use std::fmt;
+
+/// First bytes of replay file.
+const MAGIC: &[u8; 4] = b"SQRP";
+
+/// Version of replay format.
+const VERSION: u8 = 1;
+
+/// Directory for recorded replays.
+const REPLAY_DIRECTORY: &str = "user://replays";
+
+/// Command line argument with path to replay for playback.
+const REPLAY_ARGUMENT: &str = "--replay=";
+
+/// This struct store input actions of one physics tick as bit flags.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct InputFrame(u8);
+
+impl InputFrame {
+    pub const MOVE_RIGHT: Self = Self(1);
+    pub const MOVE_LEFT: Self = Self(1 << 1);
+    pub const MOVE_BACK: Self = Self(1 << 2);
+    pub const MOVE_FORWARD: Self = Self(1 << 3);
+    pub const JUMP: Self = Self(1 << 4);
+
+    /// Actions and their flags.
+    const ACTIONS: [(&'static str, Self); 5] = [
+        ("move_right", Self::MOVE_RIGHT),
+        ("move_left", Self::MOVE_LEFT),
+        ("move_back", Self::MOVE_BACK),
+        ("move_forward", Self::MOVE_FORWARD),
+        ("jump", Self::JUMP),
+    ];
+
+    /// Read pressed actions from input.
+    pub fn read(input: &Gd<Input>) -> Self {
+        let mut frame = Self::default();
+        for (action, flag) in Self::ACTIONS {
+            if input.is_action_pressed(action) {
+                frame = frame.with(flag);
+            }
+        }
+        frame
+    }
+
+    /// Return frame with pressed action.
+    pub fn with(self, flag: Self) -> Self {
+        Self(self.0 | flag.0)
+    }
+
+    /// Return true if action is pressed.
+    pub fn contains(self, flag: Self) -> bool {
+        self.0 & flag.0 != 0
+    }
+}
+
+/// This enum store all errors which can be while loading a replay or a ghost.
+#[derive(Debug)]
+pub enum ReplayError {
+    /// File not found.
+    FileNotFound(String),
+
+    /// File is not a replay or has unsupported version.
+    InvalidFormat(&'static str),
+
+    /// File can't be written.
+    Write(String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FileNotFound(path) => write!(f, "file '{path}' not found"),
+            Self::InvalidFormat(message) => write!(f, "invalid file: {message}"),
+            Self::Write(path) => write!(f, "failed to write file '{path}'"),
+        }
+    }
+}
+
+/// This struct store a recorded run.
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct Replay {
+    /// Id of the level.
+    pub level_id: String,
+
+    /// Seed of random generator at the start of the run.
+    pub seed: u64,
+
+    /// Input of the player for every physics tick.
+    pub frames: Vec<InputFrame>,
+}
+
+/// Read bytes from the start of the slice and move the slice.
+pub(crate) fn take<'a>(bytes: &mut &'a [u8], count: usize) -> Result<&'a [u8], ReplayError> {
+    if bytes.len() < count {
+        return Err(ReplayError::InvalidFormat("file is truncated"));
+    }
+
+    let (head, tail) = bytes.split_at(count);
+    *bytes = tail;
+    Ok(head)
+}
+
+impl Replay {
+    /// Encode replay to bytes, equal frames in a row are stored once.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // collect runs of equal frames
+        let mut runs: Vec<(u16, InputFrame)> = Vec::new();
+        for frame in &self.frames {
+            match runs.last_mut() {
+                Some((count, last)) if last == frame && *count < u16::MAX => *count += 1,
+                _ => runs.push((1, *frame)),
+            }
+        }
+
+        let level_id = self.level_id.as_bytes();
+        let level_id = &level_id[..level_id.len().min(u16::MAX as usize)];
+
+        let mut bytes = Vec::with_capacity(19 + level_id.len() + runs.len() * 3);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&(level_id.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(level_id);
+        bytes.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+        for (count, frame) in runs {
+            bytes.extend_from_slice(&count.to_le_bytes());
+            bytes.push(frame.0);
+        }
+
+        bytes
+    }
+
+    /// Decode replay from bytes.
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, ReplayError> {
+        let bytes = &mut bytes;
+
+        if take(bytes, MAGIC.len())? != MAGIC {
+            return Err(ReplayError::InvalidFormat("file is not a replay"));
+        }
+        if take(bytes, 1)?[0] != VERSION {
+            return Err(ReplayError::InvalidFormat("unsupported version"));
+        }
+
+        let seed = u64::from_le_bytes(take(bytes, 8)?.try_into().unwrap());
+        let level_id_length = u16::from_le_bytes(take(bytes, 2)?.try_into().unwrap());
+        let level_id = String::from_utf8(take(bytes, level_id_length as usize)?.to_vec())
+            .map_err(|_| ReplayError::InvalidFormat("level id is not UTF-8"))?;
+
+        let run_count = u32::from_le_bytes(take(bytes, 4)?.try_into().unwrap());
+        let mut frames = Vec::new();
+        for _ in 0..run_count {
+            let count = u16::from_le_bytes(take(bytes, 2)?.try_into().unwrap());
+            let frame = InputFrame(take(bytes, 1)?[0]);
+            frames.extend(std::iter::repeat_n(frame, count as usize));
+        }
+
+        Ok(Self {
+            level_id,
+            seed,
+            frames,
+        })
+    }
+
+    /// Load replay from file.
+    pub fn load(path: &str) -> Result<Self, ReplayError> {
+        if !FileAccess::file_exists(path) {
+            return Err(ReplayError::FileNotFound(path.to_string()));
+        }
+
+        Self::from_bytes(FileAccess::get_file_as_bytes(path).as_slice())
+    }
+
+    /// Save replay to file.
+    pub fn save(&self, path: &str) -> Result<(), ReplayError> {
+        let mut file =
+            FileAccess::open(path, ModeFlags::WRITE).ok_or(ReplayError::Write(path.to_string()))?;
+        file.store_buffer(&PackedByteArray::from(self.to_bytes().as_slice()));
+        file.close();
+
+        Ok(())
+    }
+}
+
+/// This enum store what happens with input of the player.
+#[derive(Default)]
+pub enum ReplayState {
+    /// Input is read from devices and not recorded.
+    #[default]
+    Off,
+
+    /// Input is read from devices and recorded.
+    Recording(Replay),
+
+    /// Input is read from replay.
+    Playing { replay: Replay, tick: usize },
+}
+
+impl ReplayState {
+    /// Input for current physics tick, 'live' reads input from devices.
+    pub fn next_frame(&mut self, live: impl FnOnce() -> InputFrame) -> InputFrame {
+        match self {
+            Self::Off => live(),
+            Self::Recording(replay) => {
+                let frame = live();
+                replay.frames.push(frame);
+                frame
+            }
+            Self::Playing { replay, tick } => {
+                // after the end of replay the player stands still
+                let frame = replay.frames.get(*tick).copied().unwrap_or_default();
+                *tick += 1;
+                frame
+            }
+        }
+    }
+}
+
+/// Path to replay from command line, if game is started for playback.
+fn playback_path() -> Option<String> {
+    Os::singleton()
+        .get_cmdline_user_args()
+        .as_slice()
+        .iter()
+        .find_map(|arg| {
+            arg.to_string()
+                .strip_prefix(REPLAY_ARGUMENT)
+                .map(String::from)
+        })
+}
+
+/// Start recording or playback of the run, must be called before any random event of the run.
+/// New run uses 'run_seed', or random seed if it is 'None'.
+/// Return seed of random generator of the run.
+pub fn start_run(player: &mut Player, level_id: &str, run_seed: Option<u64>) -> u64 {
+    // play replay from command line
+    if let Some(path) = playback_path() {
+        match Replay::load(&path) {
+            Ok(replay) => {
+                if replay.level_id != level_id {
+                    godot_warn!(
+                        "replay '{path}' is recorded on level '{}', not '{level_id}'",
+                        replay.level_id
+                    );
+                }
+
+                godot_print!("play replay '{path}' ({} ticks)", replay.frames.len());
+                let run_seed = replay.seed;
+                seed(run_seed as i64);
+                player.replay = ReplayState::Playing { replay, tick: 0 };
+                return run_seed;
+            }
+            Err(error) => godot_error!("failed to load replay: {error}"),
+        }
+    }
+
+    // record new run with given or new seed
+    let run_seed = run_seed.unwrap_or_else(|| randi() as u64);
+    seed(run_seed as i64);
+    player.replay = ReplayState::Recording(Replay {
+        level_id: level_id.to_string(),
+        seed: run_seed,
+        frames: Vec::new(),
+    });
+    run_seed
+}
+
+/// Finish the run and save recorded replay.
+pub fn finish_run(player: &mut Player) {
+    match std::mem::take(&mut player.replay) {
+        ReplayState::Recording(replay) => {
+            let error = DirAccess::make_dir_recursive_absolute(REPLAY_DIRECTORY);
+            if error != Error::OK {
+                godot_warn!("failed to create '{REPLAY_DIRECTORY}': {error:?}");
+            }
+
+            let path = format!("{REPLAY_DIRECTORY}/{}.replay", replay.level_id);
+            match replay.save(&path) {
+                Ok(()) => godot_print!("replay saved to '{path}'"),
+                Err(error) => godot_error!("{error}"),
+            }
+        }
+        ReplayState::Playing { replay, tick } => {
+            godot_print!("replay finished at tick {tick} of {}", replay.frames.len());
+        }
+        ReplayState::Off => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay(frames: Vec<InputFrame>) -> Replay {
+        Replay {
+            level_id: "arena".to_string(),
+            seed: 0xDEAD_BEEF_1234,
+            frames,
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let jump_right = InputFrame(InputFrame::JUMP.0 | InputFrame::MOVE_RIGHT.0);
+        let original = replay(vec![
+            InputFrame::default(),
+            InputFrame::MOVE_LEFT,
+            InputFrame::MOVE_LEFT,
+            jump_right,
+            InputFrame::default(),
+        ]);
+
+        let decoded = Replay::from_bytes(&original.to_bytes()).unwrap();
+        assert_eq!(decoded, original);
+    }
+
+    #[test]
+    fn equal_frames_are_stored_once() {
+        let short = replay(vec![InputFrame::MOVE_FORWARD; 1]).to_bytes();
+        let long = replay(vec![InputFrame::MOVE_FORWARD; 1000]).to_bytes();
+        assert_eq!(short.len(), long.len());
+    }
+
+    #[test]
+    fn very_long_run_is_split() {
+        let original = replay(vec![InputFrame::JUMP; u16::MAX as usize + 10]);
+        let decoded = Replay::from_bytes(&original.to_bytes()).unwrap();
+        assert_eq!(decoded.frames.len(), original.frames.len());
+    }
+
+    #[test]
+    fn truncated_file_is_error() {
+        let bytes = replay(vec![InputFrame::MOVE_BACK; 3]).to_bytes();
+        assert!(Replay::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Replay::from_bytes(b"NOPE").is_err());
+    }
+
+    #[test]
+    fn playback_stands_still_after_end() {
+        let mut state = ReplayState::Playing {
+            replay: replay(vec![InputFrame::JUMP]),
+            tick: 0,
+        };
+
+        assert_eq!(state.next_frame(InputFrame::default), InputFrame::JUMP);
+        assert_eq!(state.next_frame(InputFrame::default), InputFrame::default());
+    }
+}
@@ -0,0 +1,115 @@
+//! This module store a logic for save and load progress of the player.
+//! Progress stored in 'user://save.cfg', one section for each level.
+
+use crate::stats::RunStats;
+use godot::{classes::ConfigFile, global::Error, prelude::*};
+
+/// Path to the save file.
+const SAVE_PATH: &str = "user://save.cfg";
+
+/// This struct store which records were beaten in the run.
+#[derive(Default, Clone, Copy)]
+pub struct NewRecords {
+    /// New best score on level.
+    pub score: bool,
+
+    /// New best time on level (fastest complete or longest survival).
+    pub time: bool,
+}
+
+/// This struct store progress of the player.
+pub struct SaveData {
+    config: Gd<ConfigFile>,
+}
+
+impl SaveData {
+    /// Load progress from save file, if file not exists then progress is empty.
+    pub fn load() -> Self {
+        let mut config = ConfigFile::new_gd();
+
+        let error = config.load(SAVE_PATH);
+        if error != Error::OK && error != Error::ERR_FILE_NOT_FOUND {
+            godot_warn!("failed to load save file: {error:?}");
+        }
+
+        Self { config }
+    }
+
+    /// Write progress to save file.
+    pub fn save(&mut self) {
+        let error = self.config.save(SAVE_PATH);
+        if error != Error::OK {
+            godot_error!("failed to write save file: {error:?}");
+        }
+    }
+
+    /// Get value from save file or default value.
+    fn get<T: FromGodot + ToGodot>(&self, section: &str, key: &str, default: T) -> T {
+        self.config
+            .get_value_ex(section, key)
+            .default(&default.to_variant())
+            .done()
+            .try_to::<T>()
+            .unwrap_or(default)
+    }
+
+    /// Set value to save file (without writing to disk).
+    fn set<T: ToGodot>(&mut self, section: &str, key: &str, value: T) {
+        self.config.set_value(section, key, &value.to_variant());
+    }
+
+    /// Best score on level.
+    pub fn best_score(&self, level_id: &str) -> i64 {
+        self.get(level_id, "best_score", 0)
+    }
+
+    /// Longest time the player survived on level, in secs.
+    pub fn longest_survival(&self, level_id: &str) -> f64 {
+        self.get(level_id, "longest_survival", 0.0)
+    }
+
+    /// Fastest complete of level in secs, or 'None' if level never completed.
+    pub fn fastest_complete(&self, level_id: &str) -> Option<f64> {
+        let time = self.get(level_id, "fastest_complete", -1.0);
+        (time >= 0.0).then_some(time)
+    }
+
+    /// Return true if tutorial with given id was completed or skipped.
+    pub fn is_tutorial_completed(&self, tutorial_id: &str) -> bool {
+        self.get("tutorials", tutorial_id, false)
+    }
+
+    /// Mark tutorial as completed (without writing to disk).
+    pub fn set_tutorial_completed(&mut self, tutorial_id: &str) {
+        self.set("tutorials", tutorial_id, true);
+    }
+
+    /// Save results of the run and return which records were beaten.
+    pub fn submit_run(&mut self, level_id: &str, stats: &RunStats, completed: bool) -> NewRecords {
+        let mut records = NewRecords::default();
+
+        // update score
+        if stats.score > self.best_score(level_id) {
+            self.set(level_id, "best_score", stats.score);
+            records.score = true;
+        }
+
+        // update time
+        if completed {
+            let is_faster = self
+                .fastest_complete(level_id)
+                .is_none_or(|time| stats.time_survived < time);
+
+            if is_faster {
+                self.set(level_id, "fastest_complete", stats.time_survived);
+                records.time = true;
+            }
+        } else if stats.time_survived > self.longest_survival(level_id) {
+            self.set(level_id, "longest_survival", stats.time_survived);
+            records.time = true;
+        }
+
+        self.save();
+        records
+    }
+}
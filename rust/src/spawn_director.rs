@@ -0,0 +1,296 @@
+//! This module store a spawn director for the arena. Director runs waves
+//! of creeps: first waves are scripted, next waves are generated. Every
+//! wave has amount of creeps, mix of archetypes, spawn interval and arc of
+//! 'SpawnPath' where creeps appear. Difficulty grows with score and time,
+//! between waves the player gets a short breather.
+
+use crate::mob::archetype::random_archetype;
+use godot::{
+    classes::INode,
+    global::{randf, randf_range},
+    prelude::*,
+};
+
+/// Minimal spawn interval for any difficulty, in secs.
+const MIN_SPAWN_INTERVAL: f64 = 0.15;
+
+/// This struct describe one wave of creeps.
+#[derive(Clone)]
+pub struct Wave {
+    /// How much creeps spawn in the wave.
+    pub count: i64,
+
+    /// Archetype ids with weights, empty means all archetypes by their spawn weight.
+    pub archetypes: &'static [(&'static str, f64)],
+
+    /// Time between two spawns, in secs.
+    pub spawn_interval: f64,
+
+    /// Start of spawn arc, as progress ratio of 'SpawnPath'.
+    pub arc_start: f32,
+
+    /// Length of spawn arc, as progress ratio of 'SpawnPath' (1 is whole path).
+    pub arc_length: f32,
+}
+
+impl Wave {
+    /// Apply difficulty: more creeps and shorter interval.
+    fn with_difficulty(&self, difficulty: f64) -> Self {
+        Self {
+            count: (self.count as f64 * difficulty).round() as i64,
+            spawn_interval: (self.spawn_interval / difficulty).max(MIN_SPAWN_INTERVAL),
+            ..self.clone()
+        }
+    }
+
+    /// Choose archetype id of next creep.
+    fn random_archetype_id(&self) -> &'static str {
+        let total_weight: f64 = self.archetypes.iter().map(|(_, weight)| weight).sum();
+        if total_weight <= 0.0 {
+            return random_archetype().id;
+        }
+
+        let mut value = randf_range(0.0, total_weight);
+        for (id, weight) in self.archetypes {
+            if value < *weight {
+                return id;
+            }
+            value -= weight;
+        }
+
+        self.archetypes[0].0
+    }
+
+    /// Choose random point on spawn arc, as progress ratio of 'SpawnPath'.
+    fn random_spawn_ratio(&self) -> f32 {
+        (self.arc_start + randf() as f32 * self.arc_length).rem_euclid(1.0)
+    }
+}
+
+/// First waves of every game, next waves are generated.
+static SCRIPTED_WAVES: [Wave; 4] = [
+    Wave {
+        count: 6,
+        archetypes: &[("normal", 1.0)],
+        spawn_interval: 0.9,
+        arc_start: 0.0,
+        arc_length: 1.0,
+    },
+    Wave {
+        count: 8,
+        archetypes: &[("normal", 3.0), ("small", 1.0)],
+        spawn_interval: 0.7,
+        arc_start: 0.0,
+        arc_length: 0.5,
+    },
+    Wave {
+        count: 10,
+        archetypes: &[("normal", 2.0), ("jumper", 1.0), ("chaser", 1.0)],
+        spawn_interval: 0.6,
+        arc_start: 0.5,
+        arc_length: 0.5,
+    },
+    Wave {
+        count: 12,
+        archetypes: &[("normal", 2.0), ("armored", 1.0), ("splitter", 1.0)],
+        spawn_interval: 0.6,
+        arc_start: 0.0,
+        arc_length: 1.0,
+    },
+];
+
+/// Generate wave after scripted waves, 'number' starts from 1.
+fn generate_wave(number: i64) -> Wave {
+    let extra = (number - SCRIPTED_WAVES.len() as i64).max(0);
+
+    Wave {
+        count: 12 + 2 * extra,
+        archetypes: &[],
+        spawn_interval: 0.55,
+        arc_start: randf() as f32,
+        arc_length: randf_range(0.3, 1.0) as f32,
+    }
+}
+
+/// This enum store what director does now.
+enum DirectorState {
+    /// Director is stopped.
+    Idle,
+
+    /// Creeps of the wave are spawned.
+    Spawning { spawned: i64, time_to_next: f64 },
+
+    /// Pause before next wave.
+    Breather { time_left: f64 },
+}
+
+/// This class run waves and request spawn of creeps.
+#[derive(GodotClass)]
+#[class(base = Node)]
+pub struct SpawnDirector {
+    /// Pause between waves, in secs.
+    #[export]
+    breather_time: f64,
+
+    /// Difficulty added by every point of score.
+    #[export]
+    score_difficulty: f64,
+
+    /// Difficulty added by every second of the game.
+    #[export]
+    time_difficulty: f64,
+
+    /// Number of current wave, 0 if game not started.
+    wave_number: i64,
+
+    /// Current wave with applied difficulty.
+    wave: Option<Wave>,
+
+    /// What director does now.
+    state: DirectorState,
+
+    /// Score of current game.
+    score: i64,
+
+    /// Time of current game, in secs.
+    time: f64,
+
+    /// Factor for spawn rate from difficulty controller.
+    spawn_rate_factor: f64,
+
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for SpawnDirector {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            breather_time: 4.0,
+            score_difficulty: 0.02,
+            time_difficulty: 0.005,
+            wave_number: 0,
+            wave: None,
+            state: DirectorState::Idle,
+            score: 0,
+            time: 0.0,
+            spawn_rate_factor: 1.0,
+            base
+        }
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        match &mut self.state {
+            DirectorState::Idle => {}
+            DirectorState::Spawning {
+                spawned,
+                time_to_next,
+            } => {
+                *time_to_next -= delta;
+                if *time_to_next > 0.0 {
+                    return;
+                }
+
+                let Some(wave) = &self.wave else {
+                    return;
+                };
+
+                // spawn next creep
+                *spawned += 1;
+                *time_to_next +=
+                    (wave.spawn_interval / self.spawn_rate_factor).max(MIN_SPAWN_INTERVAL);
+                let is_wave_spawned = *spawned >= wave.count;
+
+                let archetype_id = GString::from(wave.random_archetype_id());
+                let ratio = wave.random_spawn_ratio();
+                self.signals().spawn_requested().emit(&archetype_id, ratio);
+
+                // give a breather after the wave
+                if is_wave_spawned {
+                    self.state = DirectorState::Breather {
+                        time_left: self.breather_time,
+                    };
+                    let next_wave = self.wave_number + 1;
+                    self.signals().breather_started().emit(next_wave);
+                }
+            }
+            DirectorState::Breather { time_left } => {
+                *time_left -= delta;
+                if *time_left <= 0.0 {
+                    self.start_next_wave();
+                }
+            }
+        }
+    }
+}
+
+#[godot_api]
+impl SpawnDirector {
+    /// Emitted if creep must be spawned, ratio is a progress ratio of 'SpawnPath'.
+    #[signal]
+    pub fn spawn_requested(archetype_id: GString, ratio: f32);
+
+    /// Emitted if new wave started.
+    #[signal]
+    pub fn wave_started(wave: i64);
+
+    /// Emitted if all creeps of wave spawned and breather started.
+    #[signal]
+    pub fn breather_started(next_wave: i64);
+
+    /// Start waves from the first one.
+    pub fn start(&mut self) {
+        self.wave_number = 0;
+        self.score = 0;
+        self.time = 0.0;
+        self.start_next_wave();
+    }
+
+    /// Stop waves.
+    pub fn stop(&mut self) {
+        self.state = DirectorState::Idle;
+        self.wave = None;
+    }
+
+    /// Update progress of the game, difficulty depends on it.
+    pub fn set_progress(&mut self, score: i64, time: f64) {
+        self.score = score;
+        self.time = time;
+    }
+
+    /// Set factor for spawn rate, more than 1 spawns creeps faster.
+    pub fn set_spawn_rate_factor(&mut self, factor: f64) {
+        self.spawn_rate_factor = factor;
+    }
+
+    /// Current difficulty, 1 at the start of the game.
+    #[func]
+    pub fn get_difficulty(&self) -> f64 {
+        1.0 + self.score as f64 * self.score_difficulty + self.time * self.time_difficulty
+    }
+
+    /// Number of current wave, 0 if game not started.
+    #[func]
+    pub fn get_wave_number(&self) -> i64 {
+        self.wave_number
+    }
+
+    /// Start next scripted or generated wave.
+    fn start_next_wave(&mut self) {
+        self.wave_number += 1;
+
+        let wave = usize::try_from(self.wave_number - 1)
+            .ok()
+            .and_then(|index| SCRIPTED_WAVES.get(index))
+            .cloned()
+            .unwrap_or_else(|| generate_wave(self.wave_number));
+
+        self.wave = Some(wave.with_difficulty(self.get_difficulty()));
+        self.state = DirectorState::Spawning {
+            spawned: 0,
+            time_to_next: 0.0,
+        };
+
+        let wave_number = self.wave_number;
+        self.signals().wave_started().emit(wave_number);
+    }
+}
@@ -0,0 +1,67 @@
+//! This module store statistics of one game run (from start to death
+//! or to end of level). Statistics used by summary screens and records.
+
+use crate::player::DeathCause;
+
+/// Maximum time between two squashes (in secs) to continue the combo.
+const COMBO_WINDOW: f64 = 2.0;
+
+/// This struct store statistics of current run.
+#[derive(Default, Clone)]
+pub struct RunStats {
+    /// Score of the run.
+    pub score: i64,
+
+    /// How much creeps squashed in this run.
+    pub squashed_creeps: i64,
+
+    /// Biggest amount of creeps squashed in a row.
+    pub max_combo: i64,
+
+    /// How long player is alive, in secs.
+    pub time_survived: f64,
+
+    /// Why the player die, 'None' if player alive or level complete.
+    pub death_cause: Option<DeathCause>,
+
+    /// Current amount of creeps squashed in a row.
+    combo: i64,
+
+    /// Time from last squash, in secs.
+    time_since_squash: f64,
+}
+
+impl RunStats {
+    /// Update timers, must be called every frame while player alive.
+    pub fn update(&mut self, delta: f64) {
+        self.time_survived += delta;
+        self.time_since_squash += delta;
+    }
+
+    /// Current amount of creeps squashed in a row.
+    pub fn combo(&self) -> i64 {
+        self.combo
+    }
+
+    /// Update score and combo on mob squashed, score depends on archetype of mob.
+    pub fn on_mob_squashed(&mut self, score_value: i64) {
+        // continue or reset combo
+        if self.combo > 0 && self.time_since_squash <= COMBO_WINDOW {
+            self.combo += 1;
+        } else {
+            self.combo = 1;
+        }
+        self.time_since_squash = 0.0;
+        self.max_combo = self.max_combo.max(self.combo);
+
+        // update score
+        self.squashed_creeps += 1;
+        self.score += score_value;
+    }
+}
+
+/// Format time in secs to 'mm:ss' string.
+pub fn format_time(secs: f64) -> String {
+    let secs = secs.max(0.0) as i64;
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
@@ -0,0 +1,485 @@
+//! This module store a tutorial system. Tutorial is a list of steps,
+//! every step is started by a gameplay event, show a localized prompt
+//! and wait for completion (other event or time). Completed tutorials
+//! are saved, so returning players are not taught again.
+
+use crate::{
+    event_bus::EventBus, main_scene::Improvements, player::Player, save::SaveData,
+    ui::UserInterface,
+};
+use godot::{
+    classes::{INode, InputEvent, object::ConnectFlags},
+    obj::WithBaseField,
+    prelude::*,
+};
+
+/// This enum store all events which can start or complete tutorial step.
+#[derive(Clone, Copy, PartialEq)]
+pub enum TutorialEvent {
+    /// Tutorial started.
+    Started,
+
+    /// Player started moving.
+    PlayerMoved,
+
+    /// Player jumped.
+    FirstJump,
+
+    /// Player squashed a creep.
+    FirstSquash,
+
+    /// Player got an improvement.
+    FirstPowerUp,
+}
+
+/// This enum store all prompts of tutorial, text of prompts stored in UI translations.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TutorialPrompt {
+    Move,
+    Jump,
+    Squash,
+    Goal,
+    PowerUp,
+}
+
+/// This enum store conditions of step completion.
+#[derive(Clone, Copy)]
+pub enum StepCompletion {
+    /// Step complete if event happens.
+    Event(TutorialEvent),
+
+    /// Step complete after time (in secs).
+    Time(f64),
+}
+
+/// This struct describe one step of tutorial.
+pub struct TutorialStep {
+    /// Id of step, used for select steps in 'Tutorial' node.
+    pub id: &'static str,
+
+    /// Event which starts the step.
+    pub trigger: TutorialEvent,
+
+    /// Prompt shown while step is active.
+    pub prompt: TutorialPrompt,
+
+    /// Condition of step completion.
+    pub completion: StepCompletion,
+}
+
+/// All steps of tutorials.
+static TUTORIAL_STEPS: [TutorialStep; 5] = [
+    TutorialStep {
+        id: "move",
+        trigger: TutorialEvent::Started,
+        prompt: TutorialPrompt::Move,
+        completion: StepCompletion::Event(TutorialEvent::PlayerMoved),
+    },
+    TutorialStep {
+        id: "jump",
+        trigger: TutorialEvent::Started,
+        prompt: TutorialPrompt::Jump,
+        completion: StepCompletion::Event(TutorialEvent::FirstJump),
+    },
+    TutorialStep {
+        id: "squash",
+        trigger: TutorialEvent::Started,
+        prompt: TutorialPrompt::Squash,
+        completion: StepCompletion::Event(TutorialEvent::FirstSquash),
+    },
+    TutorialStep {
+        id: "goal",
+        trigger: TutorialEvent::FirstSquash,
+        prompt: TutorialPrompt::Goal,
+        completion: StepCompletion::Time(6.0),
+    },
+    TutorialStep {
+        id: "power_up",
+        trigger: TutorialEvent::FirstPowerUp,
+        prompt: TutorialPrompt::PowerUp,
+        completion: StepCompletion::Time(5.0),
+    },
+];
+
+/// This struct store progress of tutorial, steps are done one by one.
+struct TutorialProgress {
+    /// Steps of tutorial.
+    steps: Vec<&'static TutorialStep>,
+
+    /// Index of current step.
+    current: usize,
+
+    /// Is current step started (prompt is shown).
+    is_step_active: bool,
+
+    /// How long current step is active, in secs.
+    step_time: f64,
+
+    /// All events which already happened.
+    seen_events: Vec<TutorialEvent>,
+}
+
+impl TutorialProgress {
+    fn new(steps: Vec<&'static TutorialStep>) -> Self {
+        Self {
+            steps,
+            current: 0,
+            is_step_active: false,
+            step_time: 0.0,
+            seen_events: Vec::new(),
+        }
+    }
+
+    /// Return true if all steps are done.
+    fn is_complete(&self) -> bool {
+        self.current >= self.steps.len()
+    }
+
+    /// Current step, if tutorial not complete.
+    fn current_step(&self) -> Option<&'static TutorialStep> {
+        self.steps.get(self.current).copied()
+    }
+
+    /// Register event and update steps.
+    fn on_event(&mut self, event: TutorialEvent) {
+        if !self.seen_events.contains(&event) {
+            self.seen_events.push(event);
+        }
+        self.advance();
+    }
+
+    /// Update time of current step.
+    fn update(&mut self, delta: f64) {
+        if self.is_step_active {
+            self.step_time += delta;
+            self.advance();
+        }
+    }
+
+    /// Start and complete steps while their conditions are met.
+    fn advance(&mut self) {
+        while let Some(step) = self.current_step() {
+            // start step if trigger happened
+            if !self.is_step_active {
+                if !self.seen_events.contains(&step.trigger) {
+                    return;
+                }
+                self.is_step_active = true;
+                self.step_time = 0.0;
+            }
+
+            // complete step
+            let is_done = match step.completion {
+                StepCompletion::Event(event) => self.seen_events.contains(&event),
+                StepCompletion::Time(time) => self.step_time >= time,
+            };
+            if !is_done {
+                return;
+            }
+
+            self.current += 1;
+            self.is_step_active = false;
+        }
+    }
+
+    /// Prompt which must be shown now.
+    fn active_prompt(&self) -> Option<TutorialPrompt> {
+        self.current_step()
+            .filter(|_| self.is_step_active)
+            .map(|step| step.prompt)
+    }
+}
+
+/// This class show tutorial prompts and track progress of the player.
+#[derive(GodotClass)]
+#[class(base = Node)]
+pub struct Tutorial {
+    /// Id of tutorial in save file.
+    #[export]
+    tutorial_id: GString,
+
+    /// Ids of steps of this tutorial (see 'TUTORIAL_STEPS').
+    #[export]
+    steps: PackedStringArray,
+
+    /// Input action to skip tutorial.
+    #[export]
+    skip_action: StringName,
+
+    /// Progress of tutorial, 'None' if tutorial finished or skipped.
+    progress: Option<TutorialProgress>,
+
+    /// Interface of the level, prompts are shown on it.
+    ui: Option<Gd<UserInterface>>,
+
+    /// Prompt which is shown on the interface now.
+    shown_prompt: Option<TutorialPrompt>,
+
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for Tutorial {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            tutorial_id: "tutorial".into(),
+            steps: PackedStringArray::new(),
+            skip_action: "ui_cancel".into(),
+            progress: None,
+            ui: None,
+            shown_prompt: None,
+            base
+        }
+    }
+
+    fn ready(&mut self) {
+        // returning players are not taught again
+        if SaveData::load().is_tutorial_completed(&self.tutorial_id.to_string()) {
+            return;
+        }
+
+        // select steps
+        let steps: Vec<&'static TutorialStep> = self
+            .steps
+            .as_slice()
+            .iter()
+            .filter_map(|id| {
+                let step = TUTORIAL_STEPS.iter().find(|step| *id == step.id);
+                if step.is_none() {
+                    godot_warn!("unknown tutorial step '{id}'");
+                }
+                step
+            })
+            .collect();
+        self.progress = Some(TutorialProgress::new(steps));
+
+        // missing interface is reported by the level, it can be not ready yet,
+        // so prompts are shown from 'process'
+        self.ui = self
+            .base()
+            .try_get_node_as::<UserInterface>("../UserInterface");
+
+        // connect signals from player, missing player is reported by the level
+        let Some(mut player) = self.base().try_get_node_as::<Player>("../Player") else {
+            return;
+        };
+
+        player
+            .signals()
+            .started_moving()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_player_started_moving)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        player
+            .signals()
+            .jumped()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_player_jumped)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        // subscribe to gameplay events of all creeps and power-ups
+        let bus = EventBus::singleton();
+
+        bus.signals()
+            .mob_squashed()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_mob_squashed)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        bus.signals()
+            .power_up_changed()
+            .connect_builder()
+            .object(&self.to_gd())
+            .method_mut(Self::on_power_up_changed)
+            .flags(ConnectFlags::DEFERRED)
+            .done();
+
+        self.notify(TutorialEvent::Started);
+    }
+
+    fn process(&mut self, delta: f64) {
+        if let Some(progress) = &mut self.progress {
+            progress.update(delta);
+            self.update_prompt();
+        }
+    }
+
+    fn unhandled_input(&mut self, event: Gd<InputEvent>) {
+        if self.progress.is_some() && event.is_action_pressed(&self.skip_action) {
+            self.finish();
+        }
+    }
+}
+
+#[godot_api]
+impl Tutorial {
+    /// Register gameplay event in tutorial.
+    pub fn notify(&mut self, event: TutorialEvent) {
+        if let Some(progress) = &mut self.progress {
+            progress.on_event(event);
+        }
+    }
+
+    /// Show prompt of current step if it changed or finish tutorial.
+    fn update_prompt(&mut self) {
+        let Some(progress) = &self.progress else {
+            return;
+        };
+
+        if progress.is_complete() {
+            self.finish();
+            return;
+        }
+
+        let prompt = progress.active_prompt();
+        if prompt != self.shown_prompt {
+            self.set_prompt(prompt);
+        }
+    }
+
+    /// Show prompt on the interface, 'None' hides it.
+    fn set_prompt(&mut self, prompt: Option<TutorialPrompt>) {
+        self.shown_prompt = prompt;
+        if let Some(ui) = &mut self.ui {
+            ui.bind_mut().set_tutorial_prompt(prompt);
+        }
+    }
+
+    /// Hide tutorial and save that it is completed.
+    fn finish(&mut self) {
+        self.progress = None;
+        self.set_prompt(None);
+
+        let mut save = SaveData::load();
+        save.set_tutorial_completed(&self.tutorial_id.to_string());
+        save.save();
+    }
+
+    fn on_player_started_moving(&mut self) {
+        self.notify(TutorialEvent::PlayerMoved);
+    }
+
+    fn on_player_jumped(&mut self) {
+        self.notify(TutorialEvent::FirstJump);
+    }
+
+    fn on_mob_squashed(&mut self, _score_value: i64) {
+        self.notify(TutorialEvent::FirstSquash);
+    }
+
+    fn on_power_up_changed(&mut self, power_up: Improvements) {
+        if power_up != Improvements::None {
+            self.notify(TutorialEvent::FirstPowerUp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static STEPS: [TutorialStep; 3] = [
+        TutorialStep {
+            id: "move",
+            trigger: TutorialEvent::Started,
+            prompt: TutorialPrompt::Move,
+            completion: StepCompletion::Event(TutorialEvent::PlayerMoved),
+        },
+        TutorialStep {
+            id: "goal",
+            trigger: TutorialEvent::FirstSquash,
+            prompt: TutorialPrompt::Goal,
+            completion: StepCompletion::Time(2.0),
+        },
+        TutorialStep {
+            id: "power_up",
+            trigger: TutorialEvent::FirstPowerUp,
+            prompt: TutorialPrompt::PowerUp,
+            completion: StepCompletion::Time(1.0),
+        },
+    ];
+
+    fn progress() -> TutorialProgress {
+        TutorialProgress::new(STEPS.iter().collect())
+    }
+
+    #[test]
+    fn step_starts_on_trigger() {
+        let mut progress = progress();
+        assert_eq!(progress.active_prompt(), None);
+
+        progress.on_event(TutorialEvent::Started);
+        assert_eq!(progress.active_prompt(), Some(TutorialPrompt::Move));
+    }
+
+    #[test]
+    fn steps_are_done_in_order() {
+        let mut progress = progress();
+        progress.on_event(TutorialEvent::Started);
+
+        // trigger of the next step waits until current step is complete
+        progress.on_event(TutorialEvent::FirstSquash);
+        assert_eq!(progress.active_prompt(), Some(TutorialPrompt::Move));
+
+        progress.on_event(TutorialEvent::PlayerMoved);
+        assert_eq!(progress.active_prompt(), Some(TutorialPrompt::Goal));
+    }
+
+    #[test]
+    fn completion_before_trigger_skips_step() {
+        let mut progress = progress();
+        progress.on_event(TutorialEvent::PlayerMoved);
+        progress.on_event(TutorialEvent::Started);
+
+        assert_eq!(progress.active_prompt(), None);
+        assert!(!progress.is_complete());
+    }
+
+    #[test]
+    fn time_step_completes_after_time() {
+        let mut progress = progress();
+        progress.on_event(TutorialEvent::Started);
+        progress.on_event(TutorialEvent::PlayerMoved);
+        progress.on_event(TutorialEvent::FirstSquash);
+
+        progress.update(1.5);
+        assert_eq!(progress.active_prompt(), Some(TutorialPrompt::Goal));
+        progress.update(0.5);
+        assert_eq!(progress.active_prompt(), None);
+    }
+
+    #[test]
+    fn time_is_not_counted_before_trigger() {
+        let mut progress = progress();
+        progress.on_event(TutorialEvent::Started);
+        progress.on_event(TutorialEvent::PlayerMoved);
+
+        // the step waits for the first squash
+        progress.update(10.0);
+        progress.on_event(TutorialEvent::FirstSquash);
+        assert_eq!(progress.active_prompt(), Some(TutorialPrompt::Goal));
+    }
+
+    #[test]
+    fn complete_after_all_steps() {
+        let mut progress = progress();
+        progress.on_event(TutorialEvent::Started);
+        progress.on_event(TutorialEvent::PlayerMoved);
+        progress.on_event(TutorialEvent::FirstSquash);
+        progress.on_event(TutorialEvent::FirstPowerUp);
+
+        progress.update(2.0);
+        assert_eq!(progress.active_prompt(), Some(TutorialPrompt::PowerUp));
+        assert!(!progress.is_complete());
+
+        progress.update(1.0);
+        assert!(progress.is_complete());
+    }
+}
@@ -1,8 +1,16 @@
 //! This module store a logic for UI (user interface).
 
-use crate::main_scene::Improvements;
+use crate::{
+    event_bus::{EventBus, GameState},
+    main_scene::Improvements,
+    node_refs::NodeRefs,
+    player::DeathCause,
+    save::NewRecords,
+    stats::{RunStats, format_time},
+    tutorial::TutorialPrompt,
+};
 use godot::{
-    classes::{Button, ColorRect, Control, IControl, Label},
+    classes::{AudioStreamPlayer, Button, ColorRect, Control, IControl, Label, ProgressBar},
     obj::WithBaseField,
     prelude::*,
 };
@@ -26,6 +34,30 @@ struct LanguageText {
     language_button: &'static str,
     improvement_slow_creeps: &'static str,
     improvement_shield: &'static str,
+    game_over: &'static str,
+    level_complete: &'static str,
+    creeps_squashed: &'static str,
+    max_combo: &'static str,
+    time_survived: &'static str,
+    cause_of_death: &'static str,
+    cause_creep: &'static str,
+    cause_spike: &'static str,
+    cause_fall: &'static str,
+    cause_projectile: &'static str,
+    new_score_record: &'static str,
+    new_time_record: &'static str,
+    retry_button: &'static str,
+    next_level_button: &'static str,
+    menu_button: &'static str,
+    tutorial_move: &'static str,
+    tutorial_jump: &'static str,
+    tutorial_squash: &'static str,
+    tutorial_goal: &'static str,
+    tutorial_power_up: &'static str,
+    tutorial_skip: &'static str,
+    wave: &'static str,
+    wave_breather: &'static str,
+    boss: &'static str,
 }
 
 /// This variable store translation to English.
@@ -38,6 +70,30 @@ static EN_LANGUAGE: LazyLock<LanguageText> = LazyLock::new(|| LanguageText {
     language_button: "Select language",
     improvement_shield: "Shield is active!",
     improvement_slow_creeps: "Creeps is slow!",
+    game_over: "Game over",
+    level_complete: "Level complete!",
+    creeps_squashed: "Creeps squashed",
+    max_combo: "Max combo",
+    time_survived: "Time",
+    cause_of_death: "Cause of death",
+    cause_creep: "caught by a creep",
+    cause_spike: "stepped on spikes",
+    cause_fall: "fell off the world",
+    cause_projectile: "shot by a creep",
+    new_score_record: "New score record!",
+    new_time_record: "New time record!",
+    retry_button: "Retry",
+    next_level_button: "Next level",
+    menu_button: "Menu",
+    tutorial_move: "Move with WASD or arrows.",
+    tutorial_jump: "Press SPACE to jump.",
+    tutorial_squash: "Jump on a creep to squash it,\nbut be careful: it can kill you too.",
+    tutorial_goal: "Squash all creeps to finish the level.\nAvoid spikes, jump onto platforms carefully.",
+    tutorial_power_up: "Squashed creeps sometimes give a power-up:\na shield or slow creeps.",
+    tutorial_skip: "Esc - skip tutorial",
+    wave: "Wave",
+    wave_breather: "Get ready!",
+    boss: "Boss",
 });
 
 /// This variable store translation to Russian.
@@ -50,8 +106,38 @@ static RU_LANGUAGE: LazyLock<LanguageText> = LazyLock::new(|| LanguageText {
     language_button: "Выбрать язык",
     improvement_shield: "Щит активен!",
     improvement_slow_creeps: "Жуть замедленна!",
+    game_over: "Игра окончена",
+    level_complete: "Уровень пройден!",
+    creeps_squashed: "Раздавлено жути",
+    max_combo: "Лучшее комбо",
+    time_survived: "Время",
+    cause_of_death: "Причина смерти",
+    cause_creep: "пойман жутью",
+    cause_spike: "наступил на шипы",
+    cause_fall: "упал с мира",
+    cause_projectile: "подстрелен жутью",
+    new_score_record: "Новый рекорд счета!",
+    new_time_record: "Новый рекорд времени!",
+    retry_button: "Заново",
+    next_level_button: "Следующий уровень",
+    menu_button: "Меню",
+    tutorial_move: "Движение: WASD или стрелки.",
+    tutorial_jump: "Нажмите ПРОБЕЛ, чтобы прыгнуть.",
+    tutorial_squash: "Прыгните на крипа, чтобы раздавить его,\nно будьте осторожны: он может убить и вас.",
+    tutorial_goal: "Чтобы закончить уровень, надо раздавить всю жуть.\nОпасайтесь шипов, аккуратно запрыгивайте на платформы.",
+    tutorial_power_up: "Раздавленная жуть иногда дает улучшение:\nщит или замедление жути.",
+    tutorial_skip: "Esc - пропустить обучение",
+    wave: "Волна",
+    wave_breather: "Приготовьтесь!",
+    boss: "Босс",
 });
 
+/// This enum store all type of summary screens.
+pub enum SummaryKind {
+    GameOver,
+    LevelComplete,
+}
+
 /// This class store a UI data.
 #[derive(GodotClass)]
 #[class(base = Control)]
@@ -65,6 +151,113 @@ pub struct UserInterface {
     /// Current score while player alive.
     score: i64,
 
+    /// Tutorial prompt which is shown now.
+    tutorial_prompt: Option<TutorialPrompt>,
+
+    /// Number of current wave, 0 hide the wave label.
+    wave: i64,
+
+    /// Breather before the wave is active.
+    wave_breather: bool,
+
+    /// Score of the run.
+    #[export]
+    score_label_path: NodePath,
+    score_label: OnReady<Gd<Label>>,
+
+    /// Maximum score.
+    #[export]
+    max_score_label_path: NodePath,
+    max_score_label: OnReady<Gd<Label>>,
+
+    /// Active improvement.
+    #[export]
+    improvement_label_path: NodePath,
+    improvement_label: OnReady<Gd<Label>>,
+
+    /// Prompt of the tutorial.
+    #[export]
+    tutorial_label_path: NodePath,
+    tutorial_label: OnReady<Gd<Label>>,
+
+    /// Number of the wave.
+    #[export]
+    wave_label_path: NodePath,
+    wave_label: OnReady<Gd<Label>>,
+
+    /// Health of the boss.
+    #[export]
+    boss_health_bar_path: NodePath,
+    boss_health_bar: OnReady<Gd<ProgressBar>>,
+
+    /// Name of the boss.
+    #[export]
+    boss_name_label_path: NodePath,
+    boss_name_label: OnReady<Gd<Label>>,
+
+    /// Sound of buttons.
+    #[export]
+    click_sound_path: NodePath,
+    click_sound: OnReady<Gd<AudioStreamPlayer>>,
+
+    /// Main menu.
+    #[export]
+    main_hud_path: NodePath,
+    main_hud: OnReady<Gd<ColorRect>>,
+
+    /// Logo of the game.
+    #[export]
+    name_of_game_label_path: NodePath,
+    name_of_game_label: OnReady<Gd<Label>>,
+
+    /// Button which changes language.
+    #[export]
+    language_button_path: NodePath,
+    language_button: OnReady<Gd<Button>>,
+
+    /// Button which starts the game.
+    #[export]
+    start_button_path: NodePath,
+    start_button: OnReady<Gd<Button>>,
+
+    /// Summary screen.
+    #[export]
+    summary_hud_path: NodePath,
+    summary_hud: OnReady<Gd<ColorRect>>,
+
+    /// Title of summary.
+    #[export]
+    summary_title_path: NodePath,
+    summary_title: OnReady<Gd<Label>>,
+
+    /// Statistics of the run.
+    #[export]
+    summary_stats_path: NodePath,
+    summary_stats: OnReady<Gd<Label>>,
+
+    /// Banner of new records.
+    #[export]
+    summary_record_path: NodePath,
+    summary_record: OnReady<Gd<Label>>,
+
+    /// Button which restarts the run.
+    #[export]
+    retry_button_path: NodePath,
+    retry_button: OnReady<Gd<Button>>,
+
+    /// Button which opens next level.
+    #[export]
+    next_level_button_path: NodePath,
+    next_level_button: OnReady<Gd<Button>>,
+
+    /// Button which opens main menu.
+    #[export]
+    menu_button_path: NodePath,
+    menu_button: OnReady<Gd<Button>>,
+
+    /// Errors of node references, they are reported by the scene root.
+    invalid_refs: Vec<String>,
+
     base: Base<Control>,
 }
 
@@ -75,86 +268,228 @@ impl IControl for UserInterface {
             current_language: &RU_LANGUAGE,
             max_score: 0,
             score: 0,
+            tutorial_prompt: None,
+            wave: 0,
+            wave_breather: false,
+            score_label_path: "ScoreLabel".into(),
+            score_label: OnReady::manual(),
+            max_score_label_path: "MaxScoreLabel".into(),
+            max_score_label: OnReady::manual(),
+            improvement_label_path: "Improvement".into(),
+            improvement_label: OnReady::manual(),
+            tutorial_label_path: "TutorialPrompt".into(),
+            tutorial_label: OnReady::manual(),
+            wave_label_path: "WaveLabel".into(),
+            wave_label: OnReady::manual(),
+            boss_health_bar_path: "BossHealthBar".into(),
+            boss_health_bar: OnReady::manual(),
+            boss_name_label_path: "BossHealthBar/Name".into(),
+            boss_name_label: OnReady::manual(),
+            click_sound_path: "ClickSound".into(),
+            click_sound: OnReady::manual(),
+            main_hud_path: "MainHUD".into(),
+            main_hud: OnReady::manual(),
+            name_of_game_label_path: "MainHUD/NameOfGame".into(),
+            name_of_game_label: OnReady::manual(),
+            language_button_path: "MainHUD/LanguageButton".into(),
+            language_button: OnReady::manual(),
+            start_button_path: "MainHUD/StartButton".into(),
+            start_button: OnReady::manual(),
+            summary_hud_path: "SummaryHUD".into(),
+            summary_hud: OnReady::manual(),
+            summary_title_path: "SummaryHUD/Title".into(),
+            summary_title: OnReady::manual(),
+            summary_stats_path: "SummaryHUD/Stats".into(),
+            summary_stats: OnReady::manual(),
+            summary_record_path: "SummaryHUD/Record".into(),
+            summary_record: OnReady::manual(),
+            retry_button_path: "SummaryHUD/RetryButton".into(),
+            retry_button: OnReady::manual(),
+            next_level_button_path: "SummaryHUD/NextLevelButton".into(),
+            next_level_button: OnReady::manual(),
+            menu_button_path: "SummaryHUD/MenuButton".into(),
+            menu_button: OnReady::manual(),
+            invalid_refs: Vec::new(),
             base
         }
     }
 
     fn ready(&mut self) {
+        // resolve nodes of the scene
+        let mut refs = NodeRefs::new(self.to_gd().upcast());
+        refs.resolve(&mut self.score_label, &self.score_label_path);
+        refs.resolve(&mut self.max_score_label, &self.max_score_label_path);
+        refs.resolve(&mut self.improvement_label, &self.improvement_label_path);
+        refs.resolve(&mut self.tutorial_label, &self.tutorial_label_path);
+        refs.resolve(&mut self.wave_label, &self.wave_label_path);
+        refs.resolve(&mut self.boss_health_bar, &self.boss_health_bar_path);
+        refs.resolve(&mut self.boss_name_label, &self.boss_name_label_path);
+        refs.resolve(&mut self.click_sound, &self.click_sound_path);
+        refs.resolve(&mut self.main_hud, &self.main_hud_path);
+        refs.resolve(&mut self.name_of_game_label, &self.name_of_game_label_path);
+        refs.resolve(&mut self.language_button, &self.language_button_path);
+        refs.resolve(&mut self.start_button, &self.start_button_path);
+        refs.resolve(&mut self.summary_hud, &self.summary_hud_path);
+        refs.resolve(&mut self.summary_title, &self.summary_title_path);
+        refs.resolve(&mut self.summary_stats, &self.summary_stats_path);
+        refs.resolve(&mut self.summary_record, &self.summary_record_path);
+        refs.resolve(&mut self.retry_button, &self.retry_button_path);
+        refs.resolve(&mut self.next_level_button, &self.next_level_button_path);
+        refs.resolve(&mut self.menu_button, &self.menu_button_path);
+        self.invalid_refs = refs.into_errors();
+        if !self.invalid_refs.is_empty() {
+            return;
+        }
+
         // update UI to default language
         self.update_text_from_language();
 
+        // connect 'pressed' signal for start button
+        self.start_button
+            .signals()
+            .pressed()
+            .connect_obj(self, Self::on_start_button_pressed);
+
         // connect 'pressed' signal for language button
-        self.base()
-            .get_node_as::<Button>("MainHUD/LanguageButton")
+        self.language_button
             .signals()
             .pressed()
             .connect_obj(self, Self::on_language_button_pressed);
+
+        // connect 'pressed' signals for summary buttons
+        self.retry_button
+            .signals()
+            .pressed()
+            .connect_obj(self, Self::on_retry_button_pressed);
+
+        self.next_level_button
+            .signals()
+            .pressed()
+            .connect_obj(self, Self::on_next_level_button_pressed);
+
+        self.menu_button
+            .signals()
+            .pressed()
+            .connect_obj(self, Self::on_menu_button_pressed);
+
+        // subscribe to gameplay events
+        let bus = EventBus::singleton();
+        bus.signals()
+            .score_changed()
+            .connect_obj(self, Self::on_score_changed);
+        bus.signals()
+            .power_up_changed()
+            .connect_obj(self, Self::set_improvement);
+        bus.signals()
+            .game_state_changed()
+            .connect_obj(self, Self::on_game_state_changed);
     }
 }
 
 #[godot_api]
 impl UserInterface {
+    /// Signal emit if 'StartButton' in main menu pressed.
+    #[signal]
+    pub fn start_requested();
+
+    /// Signal emit if 'RetryButton' on summary screen pressed.
+    #[signal]
+    pub fn retry_requested();
+
+    /// Signal emit if 'NextLevelButton' on summary screen pressed.
+    #[signal]
+    pub fn next_level_requested();
+
+    /// Signal emit if 'MenuButton' on summary screen pressed.
+    #[signal]
+    pub fn menu_requested();
+
+    /// Errors of node references, empty if all nodes are resolved.
+    pub fn invalid_refs(&self) -> &[String] {
+        &self.invalid_refs
+    }
+
     /// Setup interface to new language.
     fn update_text_from_language(&mut self) {
         let language = self.current_language;
 
         // update max score
-        self.base()
-            .get_node_as::<Label>("MaxScoreLabel")
+        self.max_score_label
             .set_text(&format!("{}: {}", language.max_score, self.max_score));
 
         // update score
-        self.base()
-            .get_node_as::<Label>("ScoreLabel")
+        self.score_label
             .set_text(&format!("{}: {}", language.score, self.score));
 
         // update language button
-        self.base()
-            .get_node_as::<Button>("MainHUD/LanguageButton")
+        self.language_button
             .set_text(&format!("{} (ru/en)", language.language_button));
 
         // update start button
-        self.base()
-            .get_node_as::<Button>("MainHUD/StartButton")
-            .set_text(language.start_button);
+        self.start_button.set_text(language.start_button);
 
         // update text logo
-        self.base()
-            .get_node_as::<Label>("MainHUD/NameOfGame")
-            .set_text(language.name_of_game);
+        self.name_of_game_label.set_text(language.name_of_game);
+
+        // update summary buttons
+        self.retry_button.set_text(language.retry_button);
+
+        self.next_level_button.set_text(language.next_level_button);
+
+        self.menu_button.set_text(language.menu_button);
+
+        // update tutorial prompt
+        self.update_tutorial_prompt();
+
+        // update wave label
+        self.update_wave_label();
+
+        // update boss name
+        self.boss_name_label.set_text(language.boss);
 
         // update positions for text
         match self.current_language.language {
             Languages::EN => self
-                .base()
-                .get_node_as::<Label>("MainHUD/NameOfGame")
+                .name_of_game_label
                 .set_position(Vector2::new(197.0, 190.0)),
             Languages::RU => self
-                .base()
-                .get_node_as::<Label>("MainHUD/NameOfGame")
+                .name_of_game_label
                 .set_position(Vector2::new(236.0, 192.0)),
         }
     }
 
+    /// Setup UI to new state of the game.
+    fn on_game_state_changed(&mut self, state: GameState) {
+        match state {
+            GameState::Menu => self.main_hud.show(),
+            GameState::Playing => self.start_new_game(),
+            // summary is shown with statistics of the run
+            GameState::GameOver | GameState::LevelComplete => {}
+        }
+    }
+
     /// Setup UI to startup new game.
-    pub fn start_new_game(&mut self) {
+    fn start_new_game(&mut self) {
         // set score to zero
-        self.base()
-            .get_node_as::<Label>("ScoreLabel")
+        self.score_label
             .set_text(&format!("{}: 0", self.current_language.score));
         self.score = 0;
 
-        // hide main hud
-        self.base().get_node_as::<ColorRect>("MainHUD").hide();
+        // hide main hud and summary
+        self.main_hud.hide();
+        self.summary_hud.hide();
 
         // deactivate improvements in UI
         self.set_improvement(Improvements::None);
+
+        // hide wave until first wave started
+        self.set_wave(0, false);
     }
 
     /// Setup improvement text.
     pub fn set_improvement(&mut self, improvement: Improvements) {
         // update UI
-        let mut improvement_label = self.base().get_node_as::<Label>("Improvement");
+        let mut improvement_label = self.improvement_label.clone();
         improvement_label.show();
 
         match improvement {
@@ -168,26 +503,189 @@ impl UserInterface {
         }
     }
 
-    /// Update score on mob squashed.
-    pub fn on_mob_squashed(&mut self) {
+    /// Update score of the run.
+    fn on_score_changed(&mut self, score: i64) {
         // update score
-        self.score += 1;
-        self.base()
-            .get_node_as::<Label>("ScoreLabel")
+        self.score = score;
+        self.score_label
             .set_text(&format!("{}: {}", self.current_language.score, self.score));
 
         // update max score
         if self.score > self.max_score {
             self.max_score = self.score;
-            self.base()
-                .get_node_as::<Label>("MaxScoreLabel")
-                .set_text(&format!(
-                    "{}: {}",
-                    self.current_language.max_score, self.max_score
-                ));
+            self.max_score_label.set_text(&format!(
+                "{}: {}",
+                self.current_language.max_score, self.max_score
+            ));
         }
     }
 
+    /// Set tutorial prompt, 'None' hide the prompt.
+    pub fn set_tutorial_prompt(&mut self, prompt: Option<TutorialPrompt>) {
+        if self.tutorial_prompt != prompt {
+            self.tutorial_prompt = prompt;
+            self.update_tutorial_prompt();
+        }
+    }
+
+    /// Show current tutorial prompt in current language.
+    fn update_tutorial_prompt(&mut self) {
+        let language = self.current_language;
+        let mut label = self.tutorial_label.clone();
+
+        let Some(prompt) = self.tutorial_prompt else {
+            label.hide();
+            return;
+        };
+
+        let text = match prompt {
+            TutorialPrompt::Move => language.tutorial_move,
+            TutorialPrompt::Jump => language.tutorial_jump,
+            TutorialPrompt::Squash => language.tutorial_squash,
+            TutorialPrompt::Goal => language.tutorial_goal,
+            TutorialPrompt::PowerUp => language.tutorial_power_up,
+        };
+
+        label.set_text(&format!("{text}\n({})", language.tutorial_skip));
+        label.show();
+    }
+
+    /// Set number of wave, 'breather' means the wave starts soon, 0 hide the label.
+    pub fn set_wave(&mut self, wave: i64, breather: bool) {
+        self.wave = wave;
+        self.wave_breather = breather;
+        self.update_wave_label();
+    }
+
+    /// Show current wave in current language.
+    fn update_wave_label(&mut self) {
+        let language = self.current_language;
+        let mut label = self.wave_label.clone();
+
+        if self.wave <= 0 {
+            label.hide();
+            return;
+        }
+
+        let mut text = format!("{} {}", language.wave, self.wave);
+        if self.wave_breather {
+            text += &format!("\n{}", language.wave_breather);
+        }
+
+        label.set_text(&text);
+        label.show();
+    }
+
+    /// Show health bar of the boss, 'None' hide the bar.
+    pub fn set_boss_health(&mut self, health: Option<(i64, i64)>) {
+        let mut bar = self.boss_health_bar.clone();
+
+        let Some((health, max_health)) = health else {
+            bar.hide();
+            return;
+        };
+
+        bar.set_max(max_health as f64);
+        bar.set_value(health as f64);
+        bar.show();
+    }
+
+    /// Show summary screen with statistics of the run.
+    pub fn show_summary(
+        &mut self,
+        kind: SummaryKind,
+        stats: &RunStats,
+        records: NewRecords,
+        has_next_level: bool,
+    ) {
+        let language = self.current_language;
+
+        // set title
+        let title = match kind {
+            SummaryKind::GameOver => language.game_over,
+            SummaryKind::LevelComplete => language.level_complete,
+        };
+        self.summary_title.set_text(title);
+
+        // set statistics
+        let mut text = format!(
+            "{}: {}\n{}: {}\n{}: {}\n{}: {}",
+            language.score,
+            stats.score,
+            language.creeps_squashed,
+            stats.squashed_creeps,
+            language.max_combo,
+            stats.max_combo,
+            language.time_survived,
+            format_time(stats.time_survived),
+        );
+
+        if let Some(cause) = stats.death_cause {
+            let cause = match cause {
+                DeathCause::Creep => language.cause_creep,
+                DeathCause::Spike => language.cause_spike,
+                DeathCause::Fall => language.cause_fall,
+                DeathCause::Projectile => language.cause_projectile,
+            };
+            text += &format!("\n{}: {}", language.cause_of_death, cause);
+        }
+
+        self.summary_stats.set_text(&text);
+
+        // set new records banner
+        let mut banners = Vec::new();
+        if records.score {
+            banners.push(language.new_score_record);
+        }
+        if records.time {
+            banners.push(language.new_time_record);
+        }
+
+        let mut record_label = self.summary_record.clone();
+        record_label.set_text(&banners.join("\n"));
+        record_label.set_visible(!banners.is_empty());
+
+        // show button only if next level exists
+        self.next_level_button.set_visible(has_next_level);
+
+        // hide improvement and wave, show summary
+        self.set_improvement(Improvements::None);
+        self.set_wave(0, false);
+        self.summary_hud.show();
+    }
+
+    /// Hide summary screen and play click sound.
+    fn close_summary(&mut self) {
+        self.click_sound.play();
+
+        self.summary_hud.hide();
+    }
+
+    /// Emit 'start_requested' if button pressed.
+    fn on_start_button_pressed(&mut self) {
+        self.click_sound.play();
+
+        self.signals().start_requested().emit();
+    }
+
+    /// Emit 'retry_requested' if button pressed.
+    fn on_retry_button_pressed(&mut self) {
+        self.close_summary();
+        self.signals().retry_requested().emit();
+    }
+
+    /// Emit 'next_level_requested' if button pressed.
+    fn on_next_level_button_pressed(&mut self) {
+        self.close_summary();
+        self.signals().next_level_requested().emit();
+    }
+
+    /// Emit 'menu_requested' if button pressed.
+    fn on_menu_button_pressed(&mut self) {
+        self.close_summary();
+        self.signals().menu_requested().emit();
+    }
+
     /// Set new language if button pressed.
     fn on_language_button_pressed(&mut self) {
         // set new language